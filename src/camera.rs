@@ -0,0 +1,441 @@
+use std::path::Path;
+
+use color_eyre::eyre::Context;
+use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Vertical field of view in degrees, and its limits.
+pub const DEFAULT_FOV: f32 = 45.0;
+pub const MIN_FOV: f32 = 10.0;
+pub const MAX_FOV: f32 = 120.0;
+const INITIAL_DISTANCE: f32 = 5.0;
+/// Clipping planes derived when framing, relative to the framed radius. The
+/// far plane still contains the model when fully zoomed out.
+const NEAR_PLANE_FACTOR: f32 = 0.01;
+const FAR_PLANE_FACTOR: f32 = 100.0;
+/// Smallest radius framed, so that a single point still gets a valid
+/// projection.
+const MIN_FRAMED_RADIUS: f32 = 1e-6;
+
+const PAN_SPEED: f32 = 0.001;
+const MIN_ZOOM_FACTOR: f32 = 0.1;
+const MAX_ZOOM_FACTOR: f32 = 10.0;
+
+/// Duration of the transitions started by [`Camera::transition`], in seconds.
+const TRANSITION_DURATION: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionMode {
+    Perspective,
+    Orthographic,
+}
+
+impl ProjectionMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            ProjectionMode::Perspective => ProjectionMode::Orthographic,
+            ProjectionMode::Orthographic => ProjectionMode::Perspective,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// The mouse rotates the eye around the target.
+    Orbit,
+    /// The mouse rotates the target around the eye, and the eye moves freely.
+    Fly,
+}
+
+impl CameraMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            CameraMode::Orbit => CameraMode::Fly,
+            CameraMode::Fly => CameraMode::Orbit,
+        }
+    }
+}
+
+/// Placement of a [`Camera`], saved to and restored from view files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraView {
+    pub theta: f32,
+    pub phi: f32,
+    pub zoom_factor: f32,
+    pub distance: f32,
+    pub target: Vec3,
+    /// Missing from view files saved before the camera could roll.
+    #[serde(default)]
+    pub roll: f32,
+}
+
+impl CameraView {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("cannot read view file {}", path.display()))?;
+
+        serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("invalid view file {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> color_eyre::Result<()> {
+        let contents = serde_json::to_string_pretty(self).wrap_err("cannot serialize view")?;
+
+        std::fs::write(path, contents)
+            .wrap_err_with(|| format!("cannot write view file {}", path.display()))
+    }
+}
+
+/// Interpolation between two placements, see [`Camera::transition`].
+#[derive(Debug, Clone)]
+struct Transition {
+    from: CameraView,
+    to: CameraView,
+    elapsed: f32,
+}
+
+/// Orbit camera looking at `target` from spherical coordinates around it.
+///
+/// `theta` is the azimuth around the Y axis and `phi` the elevation, both in
+/// radians. The distance to the target is `distance * zoom_factor`, so that
+/// framing a model and zooming stay independent. `roll` turns the up vector
+/// around the view direction.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    theta: f32,
+    phi: f32,
+    zoom_factor: f32,
+    distance: f32,
+    target: Vec3,
+    roll: f32,
+    /// Field of view of the perspective projection, in degrees.
+    fov: f32,
+    near_plane: f32,
+    far_plane: f32,
+    /// Clipping planes given by the user, overriding the derived ones.
+    clip_planes: (Option<f32>, Option<f32>),
+    pub projection: ProjectionMode,
+    pub mode: CameraMode,
+    transition: Option<Transition>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            theta: 0.0,
+            phi: 0.0,
+            zoom_factor: 1.0,
+            distance: INITIAL_DISTANCE,
+            target: Vec3::ZERO,
+            roll: 0.0,
+            fov: DEFAULT_FOV,
+            near_plane: NEAR_PLANE_FACTOR,
+            far_plane: FAR_PLANE_FACTOR,
+            clip_planes: (None, None),
+            projection: ProjectionMode::Perspective,
+            mode: CameraMode::Orbit,
+            transition: None,
+        }
+    }
+}
+
+impl Camera {
+    /// Fixes either clipping plane instead of deriving it when framing. When
+    /// only one is given, the other keeps the usual ratio to it.
+    pub fn set_clip_planes(&mut self, near: Option<f32>, far: Option<f32>) {
+        self.clip_planes = (near, far);
+    }
+
+    /// Looks at `center` from the distance at which a sphere of `radius` fits
+    /// in the field of view, keeping the current orientation.
+    ///
+    /// The clipping planes are fitted to `radius` as well, so that both tiny
+    /// and huge models keep enough depth precision.
+    pub fn frame(&mut self, center: Vec3, radius: f32) {
+        let radius = radius.max(MIN_FRAMED_RADIUS);
+
+        self.target = center;
+        self.distance = radius / (self.projection_fov().to_radians() * 0.5).sin();
+        self.zoom_factor = 1.0;
+
+        let ratio = FAR_PLANE_FACTOR / NEAR_PLANE_FACTOR;
+        (self.near_plane, self.far_plane) = match self.clip_planes {
+            (Some(near), Some(far)) => (near, far),
+            (Some(near), None) => (near, near * ratio),
+            (None, Some(far)) => (far / ratio, far),
+            (None, None) => (radius * NEAR_PLANE_FACTOR, radius * FAR_PLANE_FACTOR),
+        };
+    }
+
+    /// Like [`Camera::frame`], but also returns to the initial orientation.
+    pub fn reset(&mut self, center: Vec3, radius: f32) {
+        self.theta = 0.0;
+        self.phi = 0.0;
+        self.roll = 0.0;
+        self.frame(center, radius);
+    }
+
+    /// Looks at the target from the given angles, with no roll, keeping the
+    /// distance.
+    pub fn orient(&mut self, theta: f32, phi: f32) {
+        self.theta = wrap_angle(theta);
+        self.phi = phi.clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+        self.roll = 0.0;
+    }
+
+    pub fn view(&self) -> CameraView {
+        CameraView {
+            theta: self.theta,
+            phi: self.phi,
+            zoom_factor: self.zoom_factor,
+            distance: self.distance,
+            target: self.target,
+            roll: self.roll,
+        }
+    }
+
+    /// Moves to a saved placement, bringing out of range values back within
+    /// the limits of the camera.
+    pub fn set_view(&mut self, view: &CameraView) {
+        self.transition = None;
+        self.apply_view(view);
+    }
+
+    fn apply_view(&mut self, view: &CameraView) {
+        self.theta = wrap_angle(view.theta);
+        self.phi = view
+            .phi
+            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+        self.zoom_factor = view.zoom_factor.clamp(MIN_ZOOM_FACTOR, MAX_ZOOM_FACTOR);
+        self.distance = view.distance.abs();
+        self.target = view.target;
+        self.roll = wrap_angle(view.roll);
+    }
+
+    /// Applies `change` gradually over the next frames instead of at once.
+    pub fn transition(&mut self, change: impl FnOnce(&mut Self)) {
+        let from = self.view();
+        change(self);
+        let to = self.view();
+
+        self.apply_view(&from);
+        self.transition = Some(Transition {
+            from,
+            to,
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Advances the transition in progress, if any.
+    pub fn update(&mut self, delta_time: f32) {
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+
+        transition.elapsed += delta_time;
+
+        let progress = (transition.elapsed / TRANSITION_DURATION).min(1.0);
+        // Smoothstep easing, slow at both ends.
+        let t = progress * progress * (3.0 - 2.0 * progress);
+
+        let (from, to) = (&transition.from, &transition.to);
+        // Turn the short way around, since azimuths wrap.
+        let delta_theta = wrap_angle(to.theta - from.theta);
+        let delta_roll = wrap_angle(to.roll - from.roll);
+
+        let view = CameraView {
+            theta: from.theta + delta_theta * t,
+            phi: from.phi + (to.phi - from.phi) * t,
+            zoom_factor: from.zoom_factor + (to.zoom_factor - from.zoom_factor) * t,
+            distance: from.distance + (to.distance - from.distance) * t,
+            target: from.target.lerp(to.target, t),
+            roll: from.roll + delta_roll * t,
+        };
+
+        if progress >= 1.0 {
+            self.transition = None;
+        }
+
+        self.apply_view(&view);
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    /// Changes the perspective field of view, clamped to the supported range,
+    /// and returns the value actually applied.
+    pub fn set_fov(&mut self, fov: f32) -> f32 {
+        self.fov = fov.clamp(MIN_FOV, MAX_FOV);
+        self.fov
+    }
+
+    /// Field of view the current projection is built from. The orthographic
+    /// extent is sized from the default one, so that it isn't affected.
+    fn projection_fov(&self) -> f32 {
+        match self.projection {
+            ProjectionMode::Perspective => self.fov,
+            ProjectionMode::Orthographic => DEFAULT_FOV,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.distance * self.zoom_factor
+    }
+
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.target + spherical_to_cartesian(self.radius(), self.theta, self.phi)
+    }
+
+    /// Up direction of the view, the world up rolled around the view
+    /// direction.
+    pub fn up(&self) -> Vec3 {
+        let forward = (self.target - self.position()).normalize_or_zero();
+
+        Quat::from_axis_angle(forward, self.roll) * Vec3::Y
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at_rh(self.position(), self.target, self.up())
+    }
+
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
+        match self.projection {
+            ProjectionMode::Perspective => Mat4::perspective_rh_gl(
+                self.fov.to_radians(),
+                aspect_ratio,
+                self.near_plane,
+                self.far_plane,
+            ),
+            ProjectionMode::Orthographic => {
+                // Match the extent of the perspective view at the target distance.
+                let half_height = self.radius() * (DEFAULT_FOV.to_radians() * 0.5).tan();
+                let half_width = half_height * aspect_ratio;
+
+                Mat4::orthographic_rh_gl(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near_plane,
+                    self.far_plane,
+                )
+            }
+        }
+    }
+
+    /// Rotates around the target by the given azimuth and elevation deltas.
+    pub fn orbit(&mut self, delta_theta: f32, delta_phi: f32) {
+        self.transition = None;
+
+        self.theta = wrap_angle(self.theta + delta_theta);
+        self.phi =
+            (self.phi + delta_phi).clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+    }
+
+    /// Rolls the view around its direction, positive angles tilting the up
+    /// vector to the right.
+    pub fn roll(&mut self, delta: f32) {
+        self.transition = None;
+        self.roll = wrap_angle(self.roll + delta);
+    }
+
+    /// Turns the view around the eye by the given azimuth and elevation
+    /// deltas, the eye staying in place.
+    pub fn look(&mut self, delta_theta: f32, delta_phi: f32) {
+        let eye = self.position();
+        self.orbit(delta_theta, delta_phi);
+        self.target = eye - spherical_to_cartesian(self.radius(), self.theta, self.phi);
+    }
+
+    /// Moves the eye and the target together along the view direction, its
+    /// right and the world up.
+    ///
+    /// Amounts are relative to the distance to the target, so that moving
+    /// feels the same whatever the size of the model.
+    pub fn fly(&mut self, forward: f32, right: f32, up: f32) {
+        let forward_direction = (self.target - self.position()).normalize_or_zero();
+        let right_direction = forward_direction.cross(Vec3::Y).normalize_or_zero();
+
+        self.transition = None;
+
+        self.target +=
+            (forward_direction * forward + right_direction * right + Vec3::Y * up) * self.radius();
+    }
+
+    pub fn zoom(&mut self, delta: f32) {
+        self.transition = None;
+        self.zoom_factor = (self.zoom_factor + delta).clamp(MIN_ZOOM_FACTOR, MAX_ZOOM_FACTOR);
+    }
+
+    /// Zooms while keeping `point` at the same place on screen.
+    ///
+    /// Scaling the view around `point` as much as the distance to the target
+    /// keeps the direction from the eye to it, and its offset from the target
+    /// in units of the view extent.
+    pub fn zoom_toward(&mut self, delta: f32, point: Vec3) {
+        let radius = self.radius();
+        self.zoom(delta);
+
+        if radius > 0.0 {
+            self.target = point + (self.target - point) * (self.radius() / radius);
+        }
+    }
+
+    /// Moves the target in the view plane by a mouse delta in pixels.
+    ///
+    /// The speed scales with the distance to the target so that panning feels
+    /// the same at every zoom level.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let forward = (self.target - self.position()).normalize_or_zero();
+        let right = forward.cross(self.up()).normalize_or_zero();
+        let up = right.cross(forward);
+
+        self.transition = None;
+        self.target += (up * dy - right * dx) * self.radius() * PAN_SPEED;
+    }
+}
+
+/// Wraps an angle into [-PI, PI), so that azimuths accumulated over long
+/// sessions don't lose precision.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+fn spherical_to_cartesian(radius: f32, theta: f32, phi: f32) -> Vec3 {
+    let x = radius * phi.cos() * theta.cos();
+    let y = radius * phi.sin();
+    let z = radius * phi.cos() * theta.sin();
+
+    Vec3::new(x, y, z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Mesh;
+
+    #[test]
+    fn single_point_projection_is_finite() {
+        let path = std::env::temp_dir().join(format!("objviewer-point-{}.obj", std::process::id()));
+        std::fs::write(&path, "v 1 2 3\n").unwrap();
+
+        let mesh = Mesh::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mesh = mesh.unwrap();
+
+        let mut camera = Camera::default();
+        camera.frame(mesh.bounds.center(), mesh.bounds.radius());
+
+        assert!(camera.view_matrix().is_finite());
+        assert!(camera.projection_matrix(1.0).is_finite());
+    }
+}
@@ -0,0 +1,438 @@
+use std::{f32::consts::FRAC_PI_2, path::PathBuf};
+
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum, parser::ValueSource};
+use glam::{EulerRot, Quat, Vec3};
+
+use crate::{
+    camera::{DEFAULT_FOV, MAX_FOV, MIN_FOV},
+    config::Config,
+};
+
+/// A small viewer for Wavefront OBJ files.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Paths to the models to display (OBJ, STL or PLY), laid out side by
+    /// side, `-` reading an OBJ from the standard input. A directory shows
+    /// its first model, Page Up and Page Down stepping through the others
+    #[arg(default_value = "teapot.obj")]
+    pub paths: Vec<PathBuf>,
+
+    /// Render a single frame to this PNG file and exit without showing a
+    /// window
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Width of the window, or of the image rendered with --output
+    #[arg(long, value_name = "PIXELS", default_value_t = 1280, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: u32,
+
+    /// Height of the window, or of the image rendered with --output
+    #[arg(long, value_name = "PIXELS", default_value_t = 720, value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: u32,
+
+    /// Aspect ratio of the image, as W:H or a number, letterboxed within the
+    /// window or the --output size. Fills them by default
+    #[arg(long, value_name = "W:H", value_parser = parse_aspect)]
+    pub aspect: Option<f32>,
+
+    /// Print more diagnostics: -v for startup info, -vv for loading and
+    /// frame timings, -vvv for everything. RUST_LOG takes precedence
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Camera view file to restore at startup. F6 saves the view to it and
+    /// F9 restores it, using view.json when not given
+    #[arg(long, value_name = "FILE")]
+    pub view: Option<PathBuf>,
+
+    /// Configuration file with default settings, instead of
+    /// objviewer/config.toml in the user configuration directory
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Vertex shader of the meshes to use instead of the embedded one, read
+    /// again with F5
+    #[arg(long, value_name = "FILE")]
+    pub obj_vert: Option<PathBuf>,
+
+    /// Fragment shader of the meshes to use instead of the embedded one, read
+    /// again with F5
+    #[arg(long, value_name = "FILE")]
+    pub obj_frag: Option<PathBuf>,
+
+    /// Request an OpenGL debug context and print driver messages
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Start in fullscreen, which can still be toggled with F11
+    #[arg(long)]
+    pub fullscreen: bool,
+
+    /// Print the video drivers and OpenGL details for bug reports, then exit
+    /// without loading any model
+    #[arg(long)]
+    pub gl_info: bool,
+
+    /// Parse the models and print their sizes, then exit without opening a
+    /// window, failing if any of them cannot be loaded
+    #[arg(long, conflicts_with_all = ["output", "gl_info", "bench"])]
+    pub validate: bool,
+
+    /// Auto-rotate for the given duration, then print frame time statistics
+    /// and exit
+    #[arg(long, value_name = "SECONDS", value_parser = parse_positive)]
+    pub bench: Option<f32>,
+
+    /// OpenGL version to request, the shaders needing at least 3.3
+    #[arg(long, value_name = "MAJOR.MINOR", default_value = "3.3", value_parser = parse_gl_version)]
+    pub gl_version: (u8, u8),
+
+    /// OpenGL profile to request
+    #[arg(long, value_name = "PROFILE", value_enum, default_value_t = GlProfile::Core)]
+    pub gl_profile: GlProfile,
+
+    /// Disable vertical synchronization
+    #[arg(long)]
+    pub no_vsync: bool,
+
+    /// Maximum frame rate when vsync is disabled
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: Option<u32>,
+
+    /// Number of MSAA samples per pixel, 0 to disable anti-aliasing
+    #[arg(long, value_name = "N", default_value_t = 4, value_parser = parse_msaa_samples)]
+    pub msaa: u8,
+
+    /// Factor applied to the size of the models once fitted in a unit cube,
+    /// or to their original size with --no-normalize, and changed with
+    /// Shift +/-
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_positive)]
+    pub scale: f32,
+
+    /// Axis pointing up in the model files, turned upright in the viewer
+    #[arg(long, value_name = "AXIS", default_value = "y")]
+    pub up: UpAxis,
+
+    /// Rotation applied to the models after --up, as angles in degrees around
+    /// the X, Y and Z axes, in that order
+    #[arg(long, value_name = "X,Y,Z", allow_hyphen_values = true, value_parser = parse_vec3)]
+    pub rotate: Option<Vec3>,
+
+    /// Keep at most this many triangles of each model, evenly sampled, for a
+    /// quick preview of huge meshes. This leaves holes, as nothing is merged
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_triangles: Option<u64>,
+
+    /// Angle in degrees above which edges stay sharp when computing smooth
+    /// normals, from 0 to 180
+    #[arg(long, value_name = "DEGREES", default_value_t = 30.0, value_parser = parse_crease_angle)]
+    pub crease_angle: f32,
+
+    /// Start in wireframe mode, which can still be changed with W
+    #[arg(long)]
+    pub wireframe: bool,
+
+    /// Start with the mesh edges hidden, which can still be shown with E.
+    /// Their buffers are only built once shown
+    #[arg(long)]
+    pub no_edges: bool,
+
+    /// Start with the axes hidden, which can still be shown with X
+    #[arg(long)]
+    pub no_axes: bool,
+
+    /// Keep the original coordinates instead of centering the model and
+    /// fitting it in a unit cube
+    #[arg(long)]
+    pub no_normalize: bool,
+
+    /// Multiplier for the orbit speed of the mouse and arrow keys, from 0.1
+    /// to 10
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_sensitivity)]
+    pub orbit_sensitivity: f32,
+
+    /// Multiplier for the zoom step of the wheel and +/- keys, from 0.1 to 10
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_sensitivity)]
+    pub zoom_sensitivity: f32,
+
+    /// Vertical field of view in degrees, from 10 to 120, which can still be
+    /// changed with , and .
+    #[arg(long, value_name = "DEGREES", default_value_t = DEFAULT_FOV, value_parser = parse_fov)]
+    pub fov: f32,
+
+    /// Distance to the near clipping plane, derived from the model size by
+    /// default
+    #[arg(long, value_name = "DISTANCE", value_parser = parse_positive)]
+    pub near: Option<f32>,
+
+    /// Distance to the far clipping plane, derived from the model size by
+    /// default
+    #[arg(long, value_name = "DISTANCE", value_parser = parse_positive)]
+    pub far: Option<f32>,
+
+    /// Speed of the auto-rotation toggled with Space, which can still be
+    /// changed with [ and ]
+    #[arg(long, value_name = "DEG/SEC", default_value_t = 30.0, value_parser = parse_positive)]
+    pub spin_speed: f32,
+
+    /// Width of the mesh edges in pixels, antialiased
+    #[arg(long, value_name = "PIXELS", default_value_t = 2.0, value_parser = parse_positive)]
+    pub edge_width: f32,
+
+    /// Let the edges write to the depth buffer, hiding the surface behind
+    /// them
+    #[arg(long)]
+    pub edge_depth_write: bool,
+
+    /// Multiplier for the depth offset pushing the surface behind its edges,
+    /// 0 to disable
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_non_negative)]
+    pub edge_offset: f32,
+
+    /// Thickness of the outline toggled with O, relative to the model size
+    #[arg(long, value_name = "FACTOR", default_value_t = 0.01, value_parser = parse_non_negative)]
+    pub outline_width: f32,
+
+    /// Honor the transparency of the materials, blending see-through meshes
+    /// over the rest of the scene
+    #[arg(long)]
+    pub transparency: bool,
+
+    /// Size in pixels of the points of point clouds, which are files with
+    /// vertices but no faces, and of the points render mode
+    #[arg(long, value_name = "PIXELS", default_value_t = 3.0, value_parser = parse_positive)]
+    pub point_size: f32,
+
+    /// Width of the axes in pixels, antialiased
+    #[arg(long, value_name = "PIXELS", default_value_t = 1.0, value_parser = parse_positive)]
+    pub axis_width: f32,
+
+    /// Direction the key light shines towards, from above and behind the
+    /// initial view by default. Shift and the arrow keys orbit it
+    #[arg(long, value_name = "X,Y,Z", allow_hyphen_values = true, value_parser = parse_direction)]
+    pub light: Option<Vec3>,
+
+    /// Image of a lit sphere for the matcap shading mode, which L then
+    /// cycles through along with the Phong and unlit modes
+    #[arg(long, value_name = "IMAGE")]
+    pub matcap: Option<PathBuf>,
+
+    /// Solid fill color of the meshes, replacing their material, vertex and
+    /// texture colors, in the same formats as --bg
+    #[arg(long, value_name = "R,G,B", value_parser = parse_color)]
+    pub color: Option<Vec3>,
+
+    /// Background color, either as floats in 0-1 or as bytes in 0-255
+    #[arg(long, value_name = "R,G,B", default_value = "0.5,0.5,0.5", value_parser = parse_color)]
+    pub bg: Vec3,
+}
+
+impl Args {
+    /// Parses the command line, then fills the settings it leaves out from
+    /// the configuration file.
+    pub fn parse_with_config() -> color_eyre::Result<Self> {
+        let matches = Self::command().get_matches();
+        let mut args = Self::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+        let config = Config::load(args.config.as_deref())?;
+        let from_file = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        if let Some(bg) = config.bg
+            && from_file("bg")
+        {
+            args.bg = bg;
+        }
+        if let Some(sensitivity) = config.orbit_sensitivity
+            && from_file("orbit_sensitivity")
+        {
+            args.orbit_sensitivity = sensitivity;
+        }
+        if let Some(sensitivity) = config.zoom_sensitivity
+            && from_file("zoom_sensitivity")
+        {
+            args.zoom_sensitivity = sensitivity;
+        }
+        if let Some(vsync) = config.vsync
+            && from_file("no_vsync")
+        {
+            args.no_vsync = !vsync;
+        }
+        if let Some(show_edges) = config.show_edges
+            && from_file("no_edges")
+        {
+            args.no_edges = !show_edges;
+        }
+        if let Some(show_axes) = config.show_axes
+            && from_file("no_axes")
+        {
+            args.no_axes = !show_axes;
+        }
+        if let Some(fov) = config.fov
+            && from_file("fov")
+        {
+            args.fov = fov;
+        }
+
+        Ok(args)
+    }
+
+    /// Rotation from the model files to the world given by --up and --rotate.
+    pub fn model_rotation(&self) -> Quat {
+        let up = match self.up {
+            UpAxis::Y => Quat::IDENTITY,
+            UpAxis::Z => Quat::from_rotation_x(-FRAC_PI_2),
+        };
+
+        let rotate = self.rotate.map_or(Quat::IDENTITY, |angles| {
+            let [x, y, z] = angles.to_array().map(f32::to_radians);
+            Quat::from_euler(EulerRot::XYZ, x, y, z)
+        });
+
+        rotate * up
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UpAxis {
+    Y,
+    Z,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GlProfile {
+    Core,
+    /// Compatibility profile, keeping the deprecated functions
+    Compat,
+    /// OpenGL ES, which the desktop GLSL shaders don't support
+    Es,
+}
+
+fn parse_gl_version(value: &str) -> Result<(u8, u8), String> {
+    let (major, minor) = value
+        .split_once('.')
+        .ok_or_else(|| "expected MAJOR.MINOR".to_owned())?;
+
+    let major = major.parse::<u8>().map_err(|err| err.to_string())?;
+    let minor = minor.parse::<u8>().map_err(|err| err.to_string())?;
+
+    Ok((major, minor))
+}
+
+fn parse_aspect(value: &str) -> Result<f32, String> {
+    let aspect = match value.split_once(':') {
+        Some((width, height)) => {
+            let width = width.parse::<f32>().map_err(|err| err.to_string())?;
+            let height = height.parse::<f32>().map_err(|err| err.to_string())?;
+            width / height
+        }
+        None => value.parse::<f32>().map_err(|err| err.to_string())?,
+    };
+
+    if !aspect.is_finite() || aspect <= 0.0 {
+        return Err("must be a positive ratio".to_owned());
+    }
+
+    Ok(aspect)
+}
+
+fn parse_msaa_samples(value: &str) -> Result<u8, String> {
+    let samples = value.parse::<u8>().map_err(|err| err.to_string())?;
+
+    if samples != 0 && !samples.is_power_of_two() {
+        return Err("must be 0 or a power of two".to_owned());
+    }
+
+    Ok(samples)
+}
+
+fn parse_positive(value: &str) -> Result<f32, String> {
+    let number = value.parse::<f32>().map_err(|err| err.to_string())?;
+
+    if !number.is_finite() || number <= 0.0 {
+        return Err("must be a positive number".to_owned());
+    }
+
+    Ok(number)
+}
+
+fn parse_non_negative(value: &str) -> Result<f32, String> {
+    let number = value.parse::<f32>().map_err(|err| err.to_string())?;
+
+    if !number.is_finite() || number < 0.0 {
+        return Err("must be a non-negative number".to_owned());
+    }
+
+    Ok(number)
+}
+
+fn parse_crease_angle(value: &str) -> Result<f32, String> {
+    let angle = value.parse::<f32>().map_err(|err| err.to_string())?;
+
+    if !(0.0..=180.0).contains(&angle) {
+        return Err("must be between 0 and 180".to_owned());
+    }
+
+    Ok(angle)
+}
+
+fn parse_sensitivity(value: &str) -> Result<f32, String> {
+    check_sensitivity(value.parse::<f32>().map_err(|err| err.to_string())?)
+}
+
+pub fn check_sensitivity(sensitivity: f32) -> Result<f32, String> {
+    if !(0.1..=10.0).contains(&sensitivity) {
+        return Err("must be between 0.1 and 10".to_owned());
+    }
+
+    Ok(sensitivity)
+}
+
+fn parse_fov(value: &str) -> Result<f32, String> {
+    check_fov(value.parse::<f32>().map_err(|err| err.to_string())?)
+}
+
+pub fn check_fov(fov: f32) -> Result<f32, String> {
+    if !(MIN_FOV..=MAX_FOV).contains(&fov) {
+        return Err(format!("must be between {MIN_FOV} and {MAX_FOV}"));
+    }
+
+    Ok(fov)
+}
+
+/// Parses an `R,G,B` triple. Components are read as 0-255 bytes as soon as one
+/// of them is above 1, and as 0-1 floats otherwise.
+pub fn parse_color(value: &str) -> Result<Vec3, String> {
+    let color = parse_vec3(value)?;
+
+    if color.min_element() < 0.0 || color.max_element() > 255.0 {
+        return Err("components must be within 0-1 or 0-255".to_owned());
+    }
+
+    if color.max_element() > 1.0 {
+        Ok(color / 255.0)
+    } else {
+        Ok(color)
+    }
+}
+
+/// Parses an `X,Y,Z` vector into a unit direction.
+fn parse_direction(value: &str) -> Result<Vec3, String> {
+    parse_vec3(value)?
+        .try_normalize()
+        .ok_or_else(|| "must be a non-zero vector".to_owned())
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3, String> {
+    let components = value
+        .split(',')
+        .map(|component| component.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    let [x, y, z] = components[..] else {
+        return Err("expected three comma-separated components".to_owned());
+    };
+
+    Ok(Vec3::new(x, y, z))
+}
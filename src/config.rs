@@ -0,0 +1,87 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Context;
+use glam::Vec3;
+use serde::{Deserialize, Deserializer, de::Error};
+
+use crate::cli;
+
+/// Default settings read from a TOML file, each one falling back to the
+/// built-in default when left out. Command-line flags take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Background color, in the same formats as --bg.
+    #[serde(deserialize_with = "color")]
+    pub bg: Option<Vec3>,
+    #[serde(deserialize_with = "sensitivity")]
+    pub orbit_sensitivity: Option<f32>,
+    #[serde(deserialize_with = "sensitivity")]
+    pub zoom_sensitivity: Option<f32>,
+    pub vsync: Option<bool>,
+    pub show_axes: Option<bool>,
+    pub show_edges: Option<bool>,
+    /// Vertical field of view in degrees.
+    #[serde(deserialize_with = "fov")]
+    pub fov: Option<f32>,
+}
+
+impl Config {
+    /// Reads the given file, or else `objviewer/config.toml` in the user
+    /// configuration directory. Only the default file may be missing, which
+    /// gives the built-in defaults.
+    pub fn load(path: Option<&Path>) -> color_eyre::Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("cannot read {}", path.display()));
+            }
+        };
+
+        toml::from_str(&source).wrap_err_with(|| format!("cannot parse {}", path.display()))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/objviewer/config.toml`, defaulting to `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_home.join("objviewer").join("config.toml"))
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec3>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    cli::parse_color(&value).map(Some).map_err(D::Error::custom)
+}
+
+fn sensitivity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+
+    cli::check_sensitivity(value)
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+fn fov<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+
+    cli::check_fov(value).map(Some).map_err(D::Error::custom)
+}
@@ -0,0 +1,30 @@
+use glam::{Mat4, Vec3, Vec4};
+
+/// Volume seen by the camera, bounded by six planes facing inwards.
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    /// Normal in `xyz` and offset in `w`, normalized so that the dot product
+    /// with a point gives its signed distance to the plane.
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of a view-projection matrix, following Gribb and
+    /// Hartmann. Near and far assume the [-1, 1] depth range of OpenGL.
+    pub fn from_matrix(view_projection: Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|index| view_projection.row(index));
+
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2]
+            .map(|plane| plane / plane.truncate().length());
+
+        Self { planes }
+    }
+
+    /// Whether any part of a sphere may be inside the frustum. Spheres near a
+    /// corner can pass without being visible, which only costs a draw call.
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+}
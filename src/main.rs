@@ -1,35 +1,231 @@
+mod camera;
+mod cli;
+mod config;
 mod errors;
-
-use crate::errors::WrapGlErrorExt;
-use color_eyre::eyre::{Context, ContextCompat, bail};
-use glam::{Mat4, Vec3};
+mod frustum;
+mod material;
+mod mesh;
+mod picking;
+mod resources;
+mod watcher;
+
+use crate::{
+    camera::{Camera, CameraMode, CameraView},
+    errors::WrapGlErrorExt,
+    frustum::Frustum,
+    mesh::{BoundingBox, Mesh},
+    picking::Ray,
+    resources::{Buffer, ShaderProgram, Texture, VertexArray},
+    watcher::FileWatcher,
+};
+use color_eyre::eyre::{Context, ContextCompat, bail, eyre};
+use glam::{Mat4, Quat, Vec2, Vec3};
 use glow::HasContext;
-use sdl3::{event::Event, keyboard::Keycode, mouse::MouseButton};
-
-const WINDOW_WIDTH: u32 = 1280;
-const WINDOW_HEIGHT: u32 = 720;
+use sdl3::{
+    event::{Event, WindowEvent},
+    keyboard::{Keycode, Mod, Scancode},
+    mouse::MouseButton,
+    video::SwapInterval,
+};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, debug_span, error, info, trace, warn};
+use tracing_subscriber::EnvFilter;
+
+const WINDOW_TITLE: &str = "OBJ viewer";
+
+/// View file used by F6 and F9 when `--view` isn't given.
+const DEFAULT_VIEW_PATH: &str = "view.json";
+
+const FPS_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Change of the auto-rotation speed by each press of [ or ], in degrees per
+/// second.
+const SPIN_SPEED_STEP: f32 = 5.0;
+/// Orbit speed of mouse dragging, in radians per pixel.
+const MOUSE_ORBIT_SPEED: f32 = 0.005;
+const ZOOM_STEP: f32 = 0.1;
+/// Frame time while idle, with no input nor animation, redrawing at about
+/// 15 FPS to save power. Any event brings back the full rate at once.
+const IDLE_FRAME_TIME: Duration = Duration::from_millis(66);
+/// Distance in pixels the cursor may move between press and release for a
+/// left click to pick instead of orbiting.
+const CLICK_TOLERANCE: f32 = 3.0;
+const HIGHLIGHT_COLOR: Vec3 = Vec3::new(1.0, 0.5, 0.0);
+/// Factor applied to the model scale by each press of the scaling keys.
+const MODEL_SCALE_STEP: f32 = 1.1;
+/// Gap left between models laid out side by side, relative to their radius.
+const MODEL_SPACING: f32 = 0.2;
+/// Orbit speed of the arrow keys, in radians per second.
+const ORBIT_KEY_SPEED: f32 = 1.5;
+/// Change of the field of view by each press of , or ., in degrees.
+const FOV_STEP: f32 = 5.0;
+/// Roll speed of Ctrl+Q and Ctrl+E, in radians per second.
+const ROLL_SPEED: f32 = 1.0;
+/// Elevation of the top and bottom views, just short of the poles where the
+/// screen orientation would depend on rounding.
+const POLE_ELEVATION: f32 = std::f32::consts::FRAC_PI_2 - 1e-3;
+/// Speed of the fly camera, in distances to the target per second.
+const FLY_SPEED: f32 = 0.5;
+/// Direction the key light shines towards when `--light` isn't given, from
+/// above and behind the left shoulder of the initial view.
+const LIGHT_DIRECTION: Vec3 = Vec3::new(-1.0, -1.0, -0.6);
+/// Orbit speed of the light with Shift and the arrow keys, in radians per
+/// second.
+const LIGHT_ORBIT_SPEED: f32 = 1.5;
+/// Number of cells along each side of the ground grid.
+const GRID_CELLS: usize = 20;
+const GRID_COLOR: Vec3 = Vec3::splat(0.35);
+const BOUNDING_BOX_COLOR: Vec3 = Vec3::new(1.0, 1.0, 0.0);
+/// Polygon offset of the surface under the edges, as the slope factor and
+/// the units of depth resolution given to `glPolygonOffset`.
+const EDGE_OFFSET_FACTOR: f32 = 1.0;
+const EDGE_OFFSET_UNITS: f32 = 1.0;
+/// Length of the crosshair arms, relative to the window half-height.
+const CROSSHAIR_SIZE: f32 = 0.03;
+/// Length of the normal debug lines, relative to the model radius.
+const NORMAL_LINE_LENGTH: f32 = 0.05;
+
+/// Floats per interleaved mesh vertex: position, normal, color and texture
+/// coordinates with their weight.
+const VERTEX_SIZE: usize = 13;
+
+/// OpenGL functions without which nothing can be drawn, checked after loading
+/// so that a broken driver fails with their names instead of crashing in a
+/// draw call.
+const REQUIRED_GL_FUNCTIONS: &[&str] = &[
+    "glCreateShader",
+    "glShaderSource",
+    "glCompileShader",
+    "glCreateProgram",
+    "glAttachShader",
+    "glLinkProgram",
+    "glUseProgram",
+    "glGetUniformLocation",
+    "glGenBuffers",
+    "glBindBuffer",
+    "glBufferData",
+    "glGenVertexArrays",
+    "glBindVertexArray",
+    "glVertexAttribPointer",
+    "glEnableVertexAttribArray",
+    "glClear",
+    "glViewport",
+    "glDrawArrays",
+    "glDrawElements",
+];
 
-const FOV: f32 = 45.0;
-const INITIAL_CAMERA_RADIUS: f32 = 5.0;
+/// Uniform buffer binding point of the `Matrices` block shared by the
+/// programs, see [`Renderer::set_matrices`].
+const MATRICES_BINDING: u32 = 0;
 
 const OBJ_VERTEX_SHADER_SOURCE: &str = r#"
     #version 330 core
-    in vec3 position;
-    
-    uniform mat4 mvp;
+
+    layout(location = 0) in vec3 position;
+    layout(location = 1) in vec3 normal;
+    layout(location = 2) in vec4 color;
+    layout(location = 3) in vec3 tex_coord;
+
+    layout(std140) uniform Matrices {
+        mat4 mvp;
+        mat4 model;
+    };
+    uniform float point_size;
+
+    out vec3 world_position;
+    out vec3 world_normal;
+    out vec4 base_color;
+    out vec3 texture_coord;
 
     void main() {
         gl_Position = mvp * vec4(position, 1.0);
+        gl_PointSize = point_size;
+        world_position = vec3(model * vec4(position, 1.0));
+        world_normal = mat3(transpose(inverse(model))) * normal;
+        base_color = color;
+        texture_coord = tex_coord;
+    }
+"#;
+
+/// Expands each line segment, given as an instance, into a screen-aligned quad
+/// `line_width` pixels wide plus a pixel of antialiasing on each side.
+/// Drivers rarely support wide lines in core profiles, which this avoids.
+const LINE_VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    layout(location = 0) in vec3 start;
+    layout(location = 1) in vec3 end;
+    layout(location = 2) in vec3 start_color;
+    layout(location = 3) in vec3 end_color;
+
+    layout(std140) uniform Matrices {
+        mat4 mvp;
+        mat4 model;
+    };
+
+    uniform float line_width;
+    uniform vec2 viewport_size;
+
+    out vec3 line_color;
+    noperspective out float line_distance;
+
+    // Two triangles covering the segment, as coordinates along and across it.
+    const vec2 corners[6] = vec2[](
+        vec2(0.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 1.0),
+        vec2(0.0, -1.0), vec2(1.0, 1.0), vec2(0.0, 1.0)
+    );
+
+    void main() {
+        vec2 corner = corners[gl_VertexID];
+
+        vec4 clip_start = mvp * vec4(start, 1.0);
+        vec4 clip_end = mvp * vec4(end, 1.0);
+
+        // Cut the part behind the camera, which can't be projected.
+        const float min_w = 1e-4;
+        if (clip_start.w < min_w && clip_end.w < min_w) {
+            gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+            return;
+        } else if (clip_start.w < min_w) {
+            clip_start = mix(clip_start, clip_end, (min_w - clip_start.w) / (clip_end.w - clip_start.w));
+        } else if (clip_end.w < min_w) {
+            clip_end = mix(clip_end, clip_start, (min_w - clip_end.w) / (clip_start.w - clip_end.w));
+        }
+
+        vec2 half_viewport = viewport_size * 0.5;
+        vec2 delta = clip_end.xy / clip_end.w * half_viewport - clip_start.xy / clip_start.w * half_viewport;
+        vec2 direction = length(delta) > 0.0 ? normalize(delta) : vec2(1.0, 0.0);
+        vec2 normal = vec2(-direction.y, direction.x);
+
+        // Grow the quad past both ends too, to close the gaps at joints.
+        float extent = line_width * 0.5 + 1.0;
+        vec2 offset = (normal * corner.y + direction * (corner.x * 2.0 - 1.0)) * extent;
+
+        vec4 position = mix(clip_start, clip_end, corner.x);
+        position.xy += offset / half_viewport * position.w;
+
+        gl_Position = position;
+        line_color = mix(start_color, end_color, corner.x);
+        line_distance = corner.y * extent;
     }
 "#;
 
-const AXIS_VERTEX_SHADER_SOURCE: &str = r#"
+const COLORED_VERTEX_SHADER_SOURCE: &str = r#"
     #version 330 core
 
     layout(location = 0) in vec3 position;
     layout(location = 1) in vec3 color;
 
-    uniform mat4 mvp;
+    layout(std140) uniform Matrices {
+        mat4 mvp;
+        mat4 model;
+    };
 
     out vec3 vertex_color;
 
@@ -39,17 +235,114 @@ const AXIS_VERTEX_SHADER_SOURCE: &str = r#"
     }
 "#;
 
+/// Draws lines given in normalized device coordinates, ignoring the camera.
+const CROSSHAIR_VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    layout(location = 0) in vec3 position;
+
+    uniform vec2 scale;
+
+    void main() {
+        gl_Position = vec4(position.xy * scale, 0.0, 1.0);
+    }
+"#;
+
 const OBJ_FRAGMENT_SHADER_SOURCE: &str = r#"
     #version 330 core
 
+    in vec3 world_position;
+    in vec3 world_normal;
+    in vec4 base_color;
+    in vec3 texture_coord;
+
+    uniform sampler2D diffuse_map;
+    uniform bool textured;
+    uniform vec3 fill_color;
+    uniform bool use_fill_color;
+    uniform vec3 light_direction;
+    uniform vec3 view_position;
+    uniform bool lighting_enabled;
+    uniform bool matcap;
+    uniform sampler2D matcap_map;
+    uniform mat4 view;
+    uniform bool random_colors;
+    uniform bool double_sided;
+    uniform bool invert_normals;
+    uniform bool transparency;
+    // 0 draws every fragment, 1 only the opaque ones, 2 only the others.
+    uniform int alpha_pass;
+
     out vec4 vertex_color;
 
+    // Integer hash from https://nullprogram.com/blog/2018/07/31/
+    uint hash(uint x) {
+        x ^= x >> 16;
+        x *= 0x7feb352du;
+        x ^= x >> 15;
+        x *= 0x846ca68bu;
+        x ^= x >> 16;
+        return x;
+    }
+
+    vec3 triangle_color() {
+        uint h = hash(uint(gl_PrimitiveID));
+        return vec3(h & 0xffu, (h >> 8) & 0xffu, (h >> 16) & 0xffu) / 255.0;
+    }
+
     void main() {
-        vertex_color = vec4(0.41, 0.41, 0.41, 1.0);
+        // The alpha of the vertex color is the opacity of its material.
+        float opacity = transparency ? base_color.a : 1.0;
+        if ((alpha_pass == 1 && opacity < 1.0) || (alpha_pass == 2 && opacity == 1.0)) {
+            discard;
+        }
+
+        vec3 color = base_color.rgb;
+
+        if (random_colors) {
+            color = triangle_color();
+        } else if (use_fill_color) {
+            color = fill_color;
+        } else if (textured) {
+            // The third coordinate weighs the texture over the material color.
+            vec3 texel = texture(diffuse_map, texture_coord.xy).rgb;
+            color = mix(base_color.rgb, texel, texture_coord.z);
+        }
+
+        if (!lighting_enabled && !matcap) {
+            vertex_color = vec4(color, opacity);
+            return;
+        }
+
+        vec3 normal = normalize(world_normal);
+        if (invert_normals) {
+            normal = -normal;
+        }
+
+        // Light back faces as seen from behind, for open meshes.
+        if (double_sided && !gl_FrontFacing) {
+            normal = -normal;
+        }
+        // Matcaps picture a lit sphere, looked up by the view-space normal.
+        if (matcap) {
+            vec3 view_normal = normalize(mat3(view) * normal);
+            vertex_color = vec4(texture(matcap_map, view_normal.xy * 0.5 + 0.5).rgb, opacity);
+            return;
+        }
+
+        vec3 to_light = normalize(-light_direction);
+        vec3 to_view = normalize(view_position - world_position);
+        vec3 halfway = normalize(to_light + to_view);
+
+        float ambient = 0.3;
+        float diffuse = max(dot(normal, to_light), 0.0);
+        float specular = pow(max(dot(normal, halfway), 0.0), 32.0) * 0.25;
+
+        vertex_color = vec4(color * (ambient + diffuse) + vec3(specular), opacity);
     }
 "#;
 
-const AXIS_FRAGMENT_SHADER_SOURCE: &str = r#"
+const COLORED_FRAGMENT_SHADER_SOURCE: &str = r#"
     #version 330 core
 
     in vec3 vertex_color;
@@ -60,104 +353,458 @@ const AXIS_FRAGMENT_SHADER_SOURCE: &str = r#"
     }
 "#;
 
+/// Fades the border of the quads built by the line vertex shader.
+const LINE_FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    in vec3 line_color;
+    noperspective in float line_distance;
+
+    uniform float line_width;
+
+    out vec4 color;
+
+    void main() {
+        float coverage = clamp(line_width * 0.5 + 0.5 - abs(line_distance), 0.0, 1.0);
+        color = vec4(line_color, coverage);
+    }
+"#;
+
 const EDGE_FRAGMENT_SHADER_SOURCE: &str = r#"
     #version 330 core
 
-    out vec4 vertex_color;
+    noperspective in float line_distance;
+
+    uniform float line_width;
+
+    out vec4 color;
+
+    void main() {
+        float coverage = clamp(line_width * 0.5 + 0.5 - abs(line_distance), 0.0, 1.0);
+        color = vec4(0.8, 0.8, 0.8, coverage);
+    }
+"#;
+
+const CROSSHAIR_FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.8, 0.8, 0.8, 1.0);
+    }
+"#;
+
+/// Inflates the mesh along its normals, for the outline drawn behind it.
+const OUTLINE_VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    layout(location = 0) in vec3 position;
+    layout(location = 1) in vec3 normal;
+
+    layout(std140) uniform Matrices {
+        mat4 mvp;
+        mat4 model;
+    };
+
+    uniform float outline_offset;
+
+    void main() {
+        vec3 direction = length(normal) > 0.0 ? normalize(normal) : vec3(0.0);
+        gl_Position = mvp * vec4(position + direction * outline_offset, 1.0);
+    }
+"#;
+
+const OUTLINE_FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330 core
+
+    out vec4 color;
 
     void main() {
-        vertex_color = vec4(0.8, 0.8, 0.8, 1.0);
+        color = vec4(0.05, 0.05, 0.05, 1.0);
     }
 "#;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
-    let obj = wavefront::Obj::from_file("teapot.obj").wrap_err("cannot parse Wavefront file")?;
-    let obj_triangles = obj.triangles().collect::<Vec<_>>();
-    let mut vertex_data = Vec::with_capacity(obj_triangles.len() * 3);
+    let args = cli::Args::parse_with_config()?;
+    init_tracing(args.verbose);
 
-    // TODO: more rusty
-    for triangle in obj_triangles {
-        for vertex in triangle {
-            vertex_data.push(vertex.position()[0]);
-            vertex_data.push(vertex.position()[1]);
-            vertex_data.push(vertex.position()[2]);
-        }
+    if let (Some(near), Some(far)) = (args.near, args.far)
+        && near >= far
+    {
+        bail!("--near ({near}) must be smaller than --far ({far})");
     }
 
-    let edge_data = extract_edges_from_triangles(&vertex_data);
+    let load_config = LoadConfig {
+        crease_angle: args.crease_angle,
+        max_triangles: args.max_triangles.map(|max| max as usize),
+    };
+
+    // The model files of each directory, the first of which is shown.
+    let directories = args
+        .paths
+        .iter()
+        .map(|path| directory_models(path))
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+
+    if args.validate {
+        let paths = args
+            .paths
+            .iter()
+            .zip(&directories)
+            .flat_map(|(path, files)| files.clone().unwrap_or_else(|| vec![path.clone()]))
+            .collect::<Vec<_>>();
+
+        return validate_models(&paths);
+    }
+
+    let paths = args
+        .paths
+        .iter()
+        .zip(&directories)
+        .map(|(path, files)| files.as_ref().map_or(path, |files| &files[0]).clone())
+        .collect::<Vec<_>>();
+
+    // --gl-info exits as soon as the context exists, before any model is used.
+    let meshes = if args.gl_info {
+        Vec::new()
+    } else {
+        paths
+            .iter()
+            .map(|path| load_mesh(path, &load_config))
+            .collect::<color_eyre::Result<Vec<_>>>()?
+    };
 
     let sdl_context = sdl3::init().wrap_err("cannot init SDL3")?;
     let video_subsystem = sdl_context.video().wrap_err("cannot init video")?;
 
-    println!("using sdl3 {}", sdl3::version::version());
-    println!("video driver: {}", video_subsystem.current_video_driver());
+    info!("using sdl3 {}", sdl3::version::version());
+    info!("video driver: {}", video_subsystem.current_video_driver());
+
+    let (gl_major, gl_minor) = args.gl_version;
+    let gl_profile = match args.gl_profile {
+        cli::GlProfile::Core => sdl3::video::GLProfile::Core,
+        cli::GlProfile::Compat => sdl3::video::GLProfile::Compatibility,
+        cli::GlProfile::Es => sdl3::video::GLProfile::GLES,
+    };
 
     let gl_attr = video_subsystem.gl_attr();
-    gl_attr.set_context_profile(sdl3::video::GLProfile::Core);
-    gl_attr.set_context_version(3, 3);
+    gl_attr.set_context_profile(gl_profile);
+    gl_attr.set_context_version(gl_major, gl_minor);
+    gl_attr.set_framebuffer_srgb_compatible(true);
+
+    if args.debug {
+        gl_attr.set_context_flags().debug().set();
+    }
+
+    if args.msaa > 0 {
+        gl_attr.set_multisample_buffers(1);
+        gl_attr.set_multisample_samples(args.msaa);
+    }
 
-    let window = video_subsystem
-        .window("OBJ viewer", WINDOW_WIDTH, WINDOW_HEIGHT)
+    let mut window_builder = video_subsystem.window(WINDOW_TITLE, args.width, args.height);
+    // The size is in screen coordinates, which HiDPI displays scale up.
+    window_builder
         .position_centered()
-        .opengl()
-        .build()
-        .wrap_err("cannot create window")?;
+        .resizable()
+        .high_pixel_density()
+        .opengl();
+
+    // Rendering to a file still needs a context, but not a visible window.
+    if args.output.is_some() || args.gl_info {
+        window_builder.hidden();
+    } else if args.fullscreen {
+        window_builder.fullscreen();
+    }
+
+    let mut window = window_builder.build().wrap_err("cannot create window")?;
+
+    let _gl_context = window.gl_create_context().wrap_err_with(|| {
+        format!(
+            "cannot create OpenGL {gl_major}.{gl_minor} {:?} context, try another \
+                 --gl-version or --gl-profile",
+            args.gl_profile
+        )
+    })?;
+
+    let swap_interval = if args.no_vsync {
+        SwapInterval::Immediate
+    } else {
+        SwapInterval::VSync
+    };
 
-    let _gl_context = window
-        .gl_create_context()
-        .wrap_err("cannot create OpenGL context")?;
+    if let Err(err) = video_subsystem.gl_set_swap_interval(swap_interval) {
+        warn!("cannot set swap interval: {err}");
+    }
+
+    if args.fps.is_some() && !args.no_vsync {
+        warn!("--fps is ignored while vsync is enabled");
+    }
+
+    // Only cap the frame rate ourselves when vsync isn't already pacing it.
+    let min_frame_time = args
+        .fps
+        .filter(|_| args.no_vsync)
+        .map(|fps| Duration::from_secs_f64(1.0 / fps as f64));
 
-    let gl = unsafe {
+    // glow asks for every function it knows, so a few missing ones are
+    // expected: only those the viewer relies on are an error.
+    let mut missing_functions = Vec::new();
+    let mut gl = unsafe {
         glow::Context::from_loader_function(|s| {
             if let Some(proc_addr) = video_subsystem.gl_get_proc_address(s) {
                 proc_addr as *const _
             } else {
+                missing_functions.push(s.to_owned());
                 std::ptr::null()
             }
         })
     };
 
-    unsafe { gl.enable(glow::DEPTH_TEST) };
+    if !missing_functions.is_empty() {
+        debug!(
+            count = missing_functions.len(),
+            "OpenGL functions not provided by the driver: {}",
+            missing_functions.join(", ")
+        );
+    }
+
+    let missing_required = REQUIRED_GL_FUNCTIONS
+        .iter()
+        .filter(|name| missing_functions.iter().any(|missing| missing == *name))
+        .copied()
+        .collect::<Vec<_>>();
+    if !missing_required.is_empty() {
+        bail!(
+            "the OpenGL driver doesn't provide {}, check that it is installed correctly",
+            missing_required.join(", ")
+        );
+    }
+
+    unsafe {
+        info!("opengl version: {}", gl.get_parameter_string(glow::VERSION));
+        info!(
+            "opengl renderer: {}",
+            gl.get_parameter_string(glow::RENDERER)
+        );
+        info!(
+            "glsl version: {}",
+            gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION)
+        );
+    }
+
+    if args.gl_info {
+        print_gl_info(&video_subsystem, &gl);
+        return Ok(());
+    }
+
+    if args.debug {
+        enable_gl_debug_output(&mut gl);
+    }
+
+    unsafe {
+        gl.enable(glow::DEPTH_TEST);
+        // LEQUAL lets coincident geometry drawn later, such as edges, win.
+        gl.depth_func(glow::LEQUAL);
+        gl.clear_depth(1.0);
+        gl.cull_face(glow::BACK);
+        gl.front_face(glow::CCW);
+        // Core profiles only take the point size from the vertex shader.
+        gl.enable(glow::PROGRAM_POINT_SIZE);
+        gl.enable(glow::FRAMEBUFFER_SRGB);
+
+        if args.msaa > 0 {
+            gl.enable(glow::MULTISAMPLE);
+        }
+    }
+
+    // Mesh buffers, rebuilt whenever a model is reloaded
+    let mut models = paths
+        .into_iter()
+        .zip(meshes)
+        .map(|(path, (mesh, geometry))| Model::new(&gl, path, mesh, &geometry))
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+
+    for (model, files) in models.iter_mut().zip(directories) {
+        model.directory = files.map(|files| Directory { files, index: 0 });
+    }
+
+    let mut renderer = Renderer::new(
+        &gl,
+        RenderConfig {
+            background_color: args.bg,
+            mesh_color: args.color,
+            edge_width: args.edge_width,
+            axis_width: args.axis_width,
+            edge_depth_write: args.edge_depth_write,
+            edge_offset: args.edge_offset,
+            matcap: args.matcap.clone(),
+            outline_width: args.outline_width,
+            transparency: args.transparency,
+            point_size: args.point_size,
+            obj_vertex_shader: args.obj_vert.clone(),
+            obj_fragment_shader: args.obj_frag.clone(),
+        },
+    )?;
+
+    let input_config = InputConfig {
+        orbit_sensitivity: args.orbit_sensitivity,
+        zoom_sensitivity: args.zoom_sensitivity,
+    };
+
+    let normalize = !args.no_normalize;
+    let model_rotation = args.model_rotation();
+    // Applied after normalization, which would otherwise cancel it.
+    let mut model_scale = args.scale;
+    layout_models(&mut models, model_scale, normalize, model_rotation);
+
+    // The model the camera frames, cycled with Tab.
+    let mut focused_model = 0;
+
+    let mut camera = Camera::default();
+    camera.set_clip_planes(args.near, args.far);
+    camera.set_fov(args.fov);
+    let framed = models[focused_model].framed_bounds();
+    camera.frame(framed.center(), framed.radius());
+
+    if let Some(path) = &args.view {
+        match CameraView::load(path) {
+            Ok(view) => camera.set_view(&view),
+            Err(err) => warn!("{err:#}"),
+        }
+    }
+
+    let view_path = args
+        .view
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_VIEW_PATH));
+
+    let mut options = ViewOptions {
+        show_edges: !args.no_edges,
+        show_axes: !args.no_axes,
+        ..ViewOptions::default()
+    };
+    if let Some(light) = args.light {
+        options.light_direction = light;
+    }
+    if args.wireframe {
+        options.render_mode = RenderMode::Wireframe;
+    }
+
+    if let Some(output) = &args.output {
+        if options.show_edges {
+            for model in &mut models {
+                model.upload_edges(&gl)?;
+            }
+        }
+        if options.show_axes {
+            renderer.upload_axes()?;
+        }
 
-    // OBJ setup
-    let obj_program =
-        create_shader_program(&gl, OBJ_VERTEX_SHADER_SOURCE, OBJ_FRAGMENT_SHADER_SOURCE)?;
-    let (obj_vao, _obj_vbo) = create_obj_buffers(&gl, &vertex_data)?;
+        let viewport = Viewport::fit(args.width, args.height, args.aspect);
 
-    // Edges setup
-    let edges_program =
-        create_shader_program(&gl, OBJ_VERTEX_SHADER_SOURCE, EDGE_FRAGMENT_SHADER_SOURCE)?;
-    let (edges_vao, _edges_vbo) = create_edge_buffers(&gl, &edge_data)?;
+        render_to_file(&gl, args.width, args.height, viewport, output, || {
+            renderer
+                .draw(&models, &options, &camera, viewport.aspect())
+                .map(|_| ())
+        })?;
 
-    // Axis setup
-    let axis_program =
-        create_shader_program(&gl, AXIS_VERTEX_SHADER_SOURCE, AXIS_FRAGMENT_SHADER_SOURCE)?;
-    let (axis_vao, _axis_vbo) = create_axis_buffer(&gl)?;
+        println!("saved render to {}", output.display());
+        return Ok(());
+    }
 
-    let mut camera_theta = 0.0f32;
-    let mut camera_phi = 0.0f32;
-    let mut camera_zoom_factor = 1.0f32;
-    let camera_target = Vec3::ZERO;
-    let camera_up = Vec3::Y;
+    let mut screenshot_requested = false;
+    let mut show_fps = true;
+    let mut gamma_correction = true;
+    let mut auto_rotate = args.bench.is_some();
+    let mut spin_speed = args.spin_speed;
+    let mut cull_back_faces = false;
+    let mut front_face_clockwise = false;
+
+    let mut fps_frame_count = 0u32;
+    let mut fps_last_update = Instant::now();
+    // Frame timing shown in the title, refreshed every FPS_UPDATE_INTERVAL.
+    let mut fps_status = String::new();
+    let mut window_title = String::new();
+
+    // Fullscreen windows don't get the requested size. The window size is
+    // counted in pixels, as the viewport and screenshots need, whereas mouse
+    // positions come in screen coordinates, `pixel_density` times smaller.
+    let (mut window_width, mut window_height) = window.size_in_pixels();
+    let mut pixel_density = window.pixel_density();
+
+    let mut viewport = Viewport::fit(window_width, window_height, args.aspect);
+    viewport.apply(&gl);
+
+    let mut fullscreen = args.fullscreen;
+    // Size to go back to when leaving fullscreen.
+    let mut windowed_size = (args.width, args.height);
 
     let mut mouse_last_x = 0.0f32;
     let mut mouse_last_y = 0.0f32;
     let mut mouse_is_dragging = false;
+    let mut mouse_is_panning = false;
+    let mut mouse_press_position = Vec2::ZERO;
+
+    // First point of a measurement, in the scene, waiting for the second one.
+    let mut measure_start = None;
 
     let mut event_pump = sdl_context
         .event_pump()
         .wrap_err("cannot create event pump")?;
 
+    let mut benchmark = args
+        .bench
+        .map(|seconds| Benchmark::new(Duration::from_secs_f32(seconds)));
+
+    let mut last_frame = Instant::now();
+
     'running: loop {
+        let now = Instant::now();
+        let delta_time = now.duration_since(last_frame).as_secs_f32();
+        last_frame = now;
+
+        // Whether anything may change on screen, see IDLE_FRAME_TIME.
+        let mut active = false;
+
         for event in event_pump.poll_iter() {
+            active = true;
+
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
+                Event::Window {
+                    win_event: WindowEvent::PixelSizeChanged(width, height),
+                    ..
+                } => {
+                    window_width = width.max(1) as u32;
+                    window_height = height.max(1) as u32;
+                    pixel_density = window.pixel_density();
+
+                    viewport = Viewport::fit(window_width, window_height, args.aspect);
+                    viewport.apply(&gl);
+                }
+                Event::DropFile { filename, .. } => {
+                    // A dropped file replaces the focused model.
+                    match Model::load(&gl, PathBuf::from(filename), &load_config) {
+                        Ok(model) => {
+                            models[focused_model] = model;
+                            measure_start = None;
+                            layout_models(&mut models, model_scale, normalize, model_rotation);
+
+                            let framed = models[focused_model].framed_bounds();
+                            camera.transition(|camera| {
+                                camera.frame(framed.center(), framed.radius())
+                            });
+                        }
+                        Err(err) => error!("cannot load dropped file: {err:?}"),
+                    }
+                }
                 Event::MouseButtonDown {
                     x,
                     y,
@@ -165,151 +812,2190 @@ fn main() -> color_eyre::Result<()> {
                     ..
                 } => {
                     mouse_is_dragging = true;
+                    mouse_press_position = Vec2::new(x, y);
                     mouse_last_x = x;
                     mouse_last_y = y;
                 }
                 Event::MouseButtonUp {
+                    x,
+                    y,
                     mouse_btn: MouseButton::Left,
                     ..
                 } => {
                     mouse_is_dragging = false;
+
+                    // A click that didn't orbit selects the triangle under it.
+                    if mouse_press_position.distance(Vec2::new(x, y)) <= CLICK_TOLERANCE {
+                        let ray = cursor_ray(
+                            &camera,
+                            Vec2::new(x, y) * pixel_density,
+                            viewport,
+                            window_height,
+                        );
+                        let pick = pick_models(&models, &ray);
+
+                        for model in &mut models {
+                            model.highlight = None;
+                        }
+
+                        if let Some(pick) = pick {
+                            let model = &mut models[pick.model_index];
+                            model.highlight = Some(create_highlight_buffers(
+                                &gl,
+                                &model.positions,
+                                pick.triangle_index,
+                            )?);
+
+                            println!(
+                                "picked triangle {} of {}",
+                                pick.triangle_index,
+                                model.path.display()
+                            );
+                        }
+                    }
+                }
+                Event::MouseButtonDown {
+                    x,
+                    y,
+                    mouse_btn: MouseButton::Right,
+                    ..
+                } => {
+                    let ray = cursor_ray(
+                        &camera,
+                        Vec2::new(x, y) * pixel_density,
+                        viewport,
+                        window_height,
+                    );
+
+                    match (pick_models(&models, &ray), measure_start.take()) {
+                        (Some(pick), None) => {
+                            println!("measuring from {:.4}", pick.point);
+                            measure_start = Some(pick);
+                        }
+                        (Some(pick), Some(start)) => {
+                            // Measured in the coordinates of the first model,
+                            // without the normalization applied for display.
+                            let to_model = models[start.model_index].transform.inverse();
+                            let distance = to_model
+                                .transform_point3(start.point)
+                                .distance(to_model.transform_point3(pick.point));
+
+                            println!("distance: {distance:.4}");
+                        }
+                        (None, _) => println!("nothing under the cursor"),
+                    }
+                }
+                Event::MouseButtonDown {
+                    x,
+                    y,
+                    mouse_btn: MouseButton::Middle,
+                    ..
+                } => {
+                    mouse_is_panning = true;
+                    mouse_last_x = x;
+                    mouse_last_y = y;
+                }
+                Event::MouseButtonUp {
+                    mouse_btn: MouseButton::Middle,
+                    ..
+                } => {
+                    mouse_is_panning = false;
                 }
                 Event::MouseMotion { x, y, .. } => {
+                    let dx = x - mouse_last_x;
+                    let dy = y - mouse_last_y;
+
                     if mouse_is_dragging {
-                        let dx = x - mouse_last_x;
-                        let dy = y - mouse_last_y;
+                        let orbit_speed = MOUSE_ORBIT_SPEED * input_config.orbit_sensitivity;
+
+                        match camera.mode {
+                            CameraMode::Orbit => camera.orbit(dx * orbit_speed, dy * orbit_speed),
+                            CameraMode::Fly => camera.look(dx * orbit_speed, dy * orbit_speed),
+                        }
+                    }
+
+                    if mouse_is_panning {
+                        camera.pan(dx, dy);
+                    }
+
+                    mouse_last_x = x;
+                    mouse_last_y = y;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F),
+                    ..
+                } => {
+                    let framed = models[focused_model].framed_bounds();
+                    camera.transition(|camera| camera.frame(framed.center(), framed.radius()));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Tab),
+                    ..
+                } => {
+                    focused_model = (focused_model + 1) % models.len();
+
+                    let framed = models[focused_model].framed_bounds();
+                    camera.transition(|camera| camera.frame(framed.center(), framed.radius()));
+
+                    println!("focused {}", models[focused_model].path.display());
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::PageUp | Keycode::PageDown)),
+                    ..
+                } => {
+                    let step = if keycode == Keycode::PageDown { 1 } else { -1 };
+                    let model = &mut models[focused_model];
+
+                    if model.directory.is_none() {
+                        println!("{} is not browsed from a directory", model.path.display());
+                    } else if model.step_directory(&gl, step, &load_config) {
+                        measure_start = None;
+                        layout_models(&mut models, model_scale, normalize, model_rotation);
+
+                        let framed = models[focused_model].framed_bounds();
+                        camera.transition(|camera| camera.frame(framed.center(), framed.radius()));
+
+                        println!("showing {}", models[focused_model].path.display());
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::V),
+                    ..
+                } => {
+                    camera.mode = camera.mode.toggle();
+                    println!("camera mode: {:?}", camera.mode);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::L),
+                    ..
+                } => {
+                    options.shading = options.shading.next(renderer.has_matcap());
+                    println!("shading: {:?}", options.shading);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::W),
+                    ..
+                } if camera.mode == CameraMode::Orbit => {
+                    options.render_mode = options.render_mode.next();
+                    println!("render mode: {:?}", options.render_mode);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    ..
+                } => {
+                    camera.projection = camera.projection.toggle();
+                    println!("projection: {:?}", camera.projection);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::S),
+                    ..
+                } if camera.mode == CameraMode::Orbit => {
+                    screenshot_requested = true;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::H),
+                    ..
+                } => {
+                    options.flat_shading = !options.flat_shading;
+                    println!(
+                        "shading: {}",
+                        if options.flat_shading {
+                            "flat"
+                        } else {
+                            "smooth"
+                        }
+                    );
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::C),
+                    keymod,
+                    ..
+                } => {
+                    // Shift flips the winding instead, for meshes exported inside out.
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        front_face_clockwise = !front_face_clockwise;
+
+                        let front_face = if front_face_clockwise {
+                            glow::CW
+                        } else {
+                            glow::CCW
+                        };
+                        unsafe { gl.front_face(front_face) };
+
+                        println!(
+                            "front faces: {}",
+                            if front_face_clockwise { "CW" } else { "CCW" }
+                        );
+                    } else {
+                        cull_back_faces = !cull_back_faces;
+
+                        if cull_back_faces {
+                            unsafe { gl.enable(glow::CULL_FACE) };
+                        } else {
+                            unsafe { gl.disable(glow::CULL_FACE) };
+                        }
+
+                        println!(
+                            "backface culling: {}",
+                            if cull_back_faces { "on" } else { "off" }
+                        );
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
+                } => {
+                    auto_rotate = !auto_rotate;
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::LeftBracket | Keycode::RightBracket)),
+                    ..
+                } => {
+                    let step = if keycode == Keycode::RightBracket {
+                        SPIN_SPEED_STEP
+                    } else {
+                        -SPIN_SPEED_STEP
+                    };
+                    spin_speed = (spin_speed + step).max(0.0);
+
+                    println!("spin speed: {spin_speed} deg/s");
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F11),
+                    ..
+                } => {
+                    fullscreen = !fullscreen;
+
+                    if fullscreen {
+                        windowed_size = window.size();
+                    }
+
+                    // The viewport follows through the pixel size event.
+                    window
+                        .set_fullscreen(fullscreen)
+                        .wrap_err("cannot toggle fullscreen")?;
+
+                    if !fullscreen {
+                        let (width, height) = windowed_size;
+                        window
+                            .set_size(width, height)
+                            .wrap_err("cannot restore window size")?;
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F3),
+                    ..
+                } => {
+                    show_fps = !show_fps;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    ..
+                } => match renderer.reload_shaders() {
+                    Ok(()) => println!("reloaded shaders"),
+                    Err(err) => error!("cannot reload shaders: {err:?}"),
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::F6),
+                    ..
+                } => match camera.view().save(&view_path) {
+                    Ok(()) => println!("saved view to {}", view_path.display()),
+                    Err(err) => error!("{err:#}"),
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::F9),
+                    ..
+                } => match CameraView::load(&view_path) {
+                    Ok(view) => {
+                        camera.transition(|camera| camera.set_view(&view));
+                        println!("restored view from {}", view_path.display());
+                    }
+                    Err(err) => error!("{err:#}"),
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::F4),
+                    ..
+                } => {
+                    // Shaders output linear colors, which the framebuffer
+                    // encodes to sRGB while this is enabled.
+                    gamma_correction = !gamma_correction;
+
+                    if gamma_correction {
+                        unsafe { gl.enable(glow::FRAMEBUFFER_SRGB) };
+                    } else {
+                        unsafe { gl.disable(glow::FRAMEBUFFER_SRGB) };
+                    }
+
+                    println!(
+                        "gamma correction: {}",
+                        if gamma_correction { "on" } else { "off" }
+                    );
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::X),
+                    ..
+                } => {
+                    options.show_axes = !options.show_axes;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::E),
+                    keymod,
+                    ..
+                } if camera.mode == CameraMode::Orbit
+                    && !keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) =>
+                {
+                    options.show_edges = !options.show_edges;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::U),
+                    ..
+                } => {
+                    options.double_sided = !options.double_sided;
+                    println!(
+                        "double-sided lighting: {}",
+                        if options.double_sided { "on" } else { "off" }
+                    );
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::I),
+                    ..
+                } => {
+                    // Shift+C flips the winding, which inside-out meshes often
+                    // need as well for culling.
+                    options.invert_normals = !options.invert_normals;
+                    println!(
+                        "normals: {}, front faces: {}",
+                        if options.invert_normals {
+                            "inverted"
+                        } else {
+                            "as loaded"
+                        },
+                        if front_face_clockwise { "CW" } else { "CCW" }
+                    );
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    ..
+                } => {
+                    options.random_colors = !options.random_colors;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::G),
+                    ..
+                } => {
+                    options.show_grid = !options.show_grid;
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::Comma | Keycode::Period)),
+                    ..
+                } => {
+                    let step = if keycode == Keycode::Period {
+                        FOV_STEP
+                    } else {
+                        -FOV_STEP
+                    };
+                    let fov = camera.set_fov(camera.fov() + step);
+
+                    println!("field of view: {fov}°");
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::B),
+                    ..
+                } => {
+                    options.show_bounding_box = !options.show_bounding_box;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::O),
+                    ..
+                } => {
+                    options.show_outline = !options.show_outline;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::K),
+                    ..
+                } => {
+                    options.show_crosshair = !options.show_crosshair;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::N),
+                    ..
+                } => {
+                    options.show_normals = !options.show_normals;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    ..
+                } => {
+                    let framed = models[focused_model].framed_bounds();
+                    camera.transition(|camera| camera.reset(framed.center(), framed.radius()));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Equals | Keycode::Plus | Keycode::KpPlus),
+                    keymod,
+                    ..
+                } => {
+                    // Shift scales the model instead of zooming.
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        model_scale *= MODEL_SCALE_STEP;
+                        layout_models(&mut models, model_scale, normalize, model_rotation);
+                        println!("model scale: {model_scale:.3}");
+                    } else {
+                        camera.zoom(-ZOOM_STEP * input_config.zoom_sensitivity);
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Minus | Keycode::Underscore | Keycode::KpMinus),
+                    keymod,
+                    ..
+                } => {
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        model_scale /= MODEL_SCALE_STEP;
+                        layout_models(&mut models, model_scale, normalize, model_rotation);
+                        println!("model scale: {model_scale:.3}");
+                    } else {
+                        camera.zoom(ZOOM_STEP * input_config.zoom_sensitivity);
+                    }
+                }
+                Event::MouseWheel {
+                    y,
+                    mouse_x,
+                    mouse_y,
+                    ..
+                } => {
+                    let ray = cursor_ray(
+                        &camera,
+                        Vec2::new(mouse_x, mouse_y) * pixel_density,
+                        viewport,
+                        window_height,
+                    );
+                    let anchor = zoom_anchor(&camera, &models, &ray);
+
+                    if y > 0.0 {
+                        camera.zoom_toward(-ZOOM_STEP * input_config.zoom_sensitivity, anchor);
+                    } else {
+                        camera.zoom_toward(ZOOM_STEP * input_config.zoom_sensitivity, anchor);
+                    }
+                }
+                // Preset views come last, as this arm matches any key.
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some((name, theta, phi)) = preset_view(keycode) {
+                        camera.transition(|camera| camera.orient(theta, phi));
+                        println!("view: {name}");
+                    }
+                }
+                _ => {}
+            };
+        }
+
+        let mut models_reloaded = false;
+
+        for model in &mut models {
+            if model.file_watcher.as_mut().is_some_and(FileWatcher::poll) {
+                match model.reload(&gl, &load_config) {
+                    Ok(changed) => models_reloaded |= changed,
+                    Err(err) => error!("cannot reload model: {err:?}"),
+                }
+            }
+        }
+
+        // A reloaded model may have changed size and pushed its neighbors.
+        if models_reloaded {
+            active = true;
+            layout_models(&mut models, model_scale, normalize, model_rotation);
+        }
+
+        // Arrow keys are polled rather than handled as events so that holding
+        // them orbits at a fixed rate, independent of key repeat and FPS.
+        let keyboard = event_pump.keyboard_state();
+        active |= keyboard.pressed_scancodes().next().is_some();
+
+        let arrow = |positive, negative| {
+            keyboard.is_scancode_pressed(positive) as i32 as f32
+                - keyboard.is_scancode_pressed(negative) as i32 as f32
+        };
+        let (arrow_x, arrow_y) = (
+            arrow(Scancode::Right, Scancode::Left),
+            arrow(Scancode::Up, Scancode::Down),
+        );
+
+        let shift_pressed = keyboard.is_scancode_pressed(Scancode::LShift)
+            || keyboard.is_scancode_pressed(Scancode::RShift);
+
+        // Shift turns the arrow keys to the light.
+        if shift_pressed {
+            let light_step = LIGHT_ORBIT_SPEED * delta_time;
+
+            if arrow_x != 0.0 || arrow_y != 0.0 {
+                // Up raises the light, which then shines further down.
+                options.light_direction = -orbit_direction(
+                    -options.light_direction,
+                    arrow_x * light_step,
+                    arrow_y * light_step,
+                );
+            }
+        } else {
+            let orbit_step = ORBIT_KEY_SPEED * input_config.orbit_sensitivity * delta_time;
+
+            if arrow_x != 0.0 || arrow_y != 0.0 {
+                camera.orbit(arrow_x * orbit_step, arrow_y * orbit_step);
+            }
+        }
+
+        let ctrl_pressed = keyboard.is_scancode_pressed(Scancode::LCtrl)
+            || keyboard.is_scancode_pressed(Scancode::RCtrl);
+
+        // Ctrl+Q and Ctrl+E roll, leaving Q and E to the fly camera.
+        if ctrl_pressed {
+            let roll_step = ROLL_SPEED * delta_time;
+
+            if keyboard.is_scancode_pressed(Scancode::Q) {
+                camera.roll(-roll_step);
+            }
+            if keyboard.is_scancode_pressed(Scancode::E) {
+                camera.roll(roll_step);
+            }
+        }
+
+        // WASD moves the fly camera, with Q and E for down and up. The same
+        // keys toggle settings in orbit mode, which are ignored while flying.
+        if camera.mode == CameraMode::Fly && !ctrl_pressed {
+            let fly_step = FLY_SPEED * delta_time;
+            let axis = |positive, negative| {
+                keyboard.is_scancode_pressed(positive) as i32 as f32
+                    - keyboard.is_scancode_pressed(negative) as i32 as f32
+            };
+
+            let movement = Vec3::new(
+                axis(Scancode::W, Scancode::S),
+                axis(Scancode::D, Scancode::A),
+                axis(Scancode::E, Scancode::Q),
+            ) * fly_step;
+
+            // Flying cancels transitions, so only fly when a key is held.
+            if movement != Vec3::ZERO {
+                camera.fly(movement.x, movement.y, movement.z);
+            }
+        }
+
+        active |= camera.is_transitioning();
+        camera.update(delta_time);
+
+        if auto_rotate
+            && camera.mode == CameraMode::Orbit
+            && !camera.is_transitioning()
+            && !mouse_is_dragging
+            && !mouse_is_panning
+        {
+            active = true;
+            camera.orbit(spin_speed.to_radians() * delta_time, 0.0);
+        }
+
+        // Edges and axes are uploaded on demand, as toggling them on or
+        // reloading a model may leave them missing.
+        if options.show_edges {
+            for model in &mut models {
+                model.upload_edges(&gl)?;
+            }
+        }
+        if options.show_axes {
+            renderer.upload_axes()?;
+        }
+
+        let culled_models = renderer.draw(&models, &options, &camera, viewport.aspect())?;
+
+        if screenshot_requested {
+            screenshot_requested = false;
+
+            let path = screenshot_path();
+            match save_screenshot(&gl, window_width, window_height, &path) {
+                Ok(()) => println!("saved screenshot to {}", path.display()),
+                Err(err) => error!("{err:?}"),
+            }
+        }
+
+        window.gl_swap_window();
+
+        if let Some(min_frame_time) = min_frame_time {
+            let frame_time = now.elapsed();
+            if frame_time < min_frame_time {
+                std::thread::sleep(min_frame_time - frame_time);
+            }
+        }
+
+        // The benchmark measures the full rate, which it always animates.
+        if !active && benchmark.is_none() {
+            let frame_time = now.elapsed();
+            if frame_time < IDLE_FRAME_TIME {
+                let timeout = (IDLE_FRAME_TIME - frame_time).as_millis() as i32;
+
+                // Waits without taking the event, which the next frame handles.
+                unsafe { sdl3::sys::events::SDL_WaitEventTimeout(std::ptr::null_mut(), timeout) };
+            }
+        }
+
+        trace!(
+            frame_time_ms = now.elapsed().as_secs_f32() * 1000.0,
+            "frame"
+        );
+
+        if let Some(benchmark) = &mut benchmark {
+            benchmark.frame_times.push(now.elapsed());
+
+            if benchmark.start.elapsed() >= benchmark.duration {
+                benchmark.report();
+                break 'running;
+            }
+        }
+
+        fps_frame_count += 1;
+
+        let fps_elapsed = fps_last_update.elapsed();
+        if fps_elapsed >= FPS_UPDATE_INTERVAL {
+            let fps = fps_frame_count as f32 / fps_elapsed.as_secs_f32();
+            let frame_time_ms = 1000.0 / fps;
+
+            debug!(fps, frame_time_ms, "frame timing");
+
+            fps_status = format!("{fps:.0} FPS ({frame_time_ms:.2} ms)");
+
+            if models.len() > 1 {
+                fps_status += &format!(" - {culled_models}/{} culled", models.len());
+            }
+
+            fps_frame_count = 0;
+            fps_last_update = Instant::now();
+        }
+
+        // Only touch the title when it changes, which most frames don't.
+        let mut title = status_line(&models, focused_model, &options);
+        if show_fps && !fps_status.is_empty() {
+            title += &format!(" - {fps_status}");
+        }
+
+        if title != window_title {
+            window
+                .set_title(&title)
+                .wrap_err("cannot set window title")?;
+            window_title = title;
+        }
+    }
+
+    Ok(())
+}
+
+/// Window title describing the focused model and the main display settings.
+fn status_line(models: &[Model], focused_model: usize, options: &ViewOptions) -> String {
+    let model = &models[focused_model];
+    let on_off = |enabled| if enabled { "on" } else { "off" };
+
+    let name = model.path.file_name().map_or_else(
+        || model.path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+
+    let mut status = format!("{WINDOW_TITLE} - {name}");
+    if let Some(directory) = &model.directory {
+        status += &format!(" [{}/{}]", directory.index + 1, directory.files.len());
+    }
+    if models.len() > 1 {
+        status += &format!(" ({}/{})", focused_model + 1, models.len());
+    }
+
+    let size = if model.point_cloud {
+        format!("{} points", model.point_count())
+    } else {
+        format!("{} triangles", model.triangle_count())
+    };
+
+    status += &format!(
+        " - {size} - {:?} - edges {} - axes {}",
+        options.render_mode,
+        on_off(options.show_edges),
+        on_off(options.show_axes)
+    );
+
+    status
+}
+
+/// Logs to stderr, at a level picked by the number of `-v` flags unless
+/// `RUST_LOG` is set.
+fn init_tracing(verbosity: u8) {
+    let level = match verbosity {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,{}={level}", env!("CARGO_CRATE_NAME"))));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Loads a model file, prepares its geometry for upload and prints a summary.
+fn load_mesh(path: &Path, config: &LoadConfig) -> color_eyre::Result<(Mesh, MeshGeometry)> {
+    let _span = debug_span!("load", path = %path.display()).entered();
+
+    let mut mesh = Mesh::from_file(path)?;
+
+    if let Some(max_triangles) = config.max_triangles {
+        let triangle_count = mesh.triangle_count();
+        if triangle_count > max_triangles {
+            mesh.subsample(max_triangles);
+            println!(
+                "preview of {}: kept {max_triangles} of {triangle_count} triangles",
+                path.display()
+            );
+        }
+    }
+
+    let geometry = MeshGeometry::new(&mesh, config.crease_angle);
+
+    let size = mesh.bounds.max - mesh.bounds.min;
+
+    info!(
+        vertices = geometry.vertices.len() / VERTEX_SIZE,
+        vertices_before_deduplication = mesh.vertex_count(),
+        triangles = mesh.triangle_count(),
+        // Only counted when logged, the edges being uploaded once shown.
+        edges = if mesh.point_cloud {
+            0
+        } else {
+            mesh::count_edges(&mesh.positions)
+        },
+        size = %format!("{:.3} x {:.3} x {:.3}", size.x, size.y, size.z),
+        "loaded {}",
+        path.display()
+    );
+
+    Ok((mesh, geometry))
+}
+
+/// Supported model files of a directory, sorted by name, or `None` for any
+/// other path.
+fn directory_models(path: &Path) -> color_eyre::Result<Option<Vec<PathBuf>>> {
+    if !path.is_dir() {
+        return Ok(None);
+    }
+
+    let mut files = Vec::new();
+
+    for entry in std::fs::read_dir(path)
+        .wrap_err_with(|| format!("cannot read directory {}", path.display()))?
+    {
+        let file = entry
+            .wrap_err_with(|| format!("cannot read directory {}", path.display()))?
+            .path();
+
+        let supported = file
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| {
+                mesh::EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+            });
+
+        if supported && file.is_file() {
+            files.push(file);
+        }
+    }
+
+    if files.is_empty() {
+        bail!("no model files in {}", path.display());
+    }
+
+    files.sort();
+
+    Ok(Some(files))
+}
+
+/// Parses every model without any window or OpenGL context, printing its size
+/// or why it failed, for checking assets in scripts.
+///
+/// Textures are only probed, and problems with them are warnings as in the
+/// viewer.
+fn validate_models(paths: &[PathBuf]) -> color_eyre::Result<()> {
+    let mut failures = 0;
+
+    for path in paths {
+        let mesh = match Mesh::from_file(path) {
+            Ok(mesh) => mesh,
+            Err(err) => {
+                error!("{err:#}");
+                failures += 1;
+                continue;
+            }
+        };
+
+        if let Some(texture) = &mesh.texture
+            && let Err(err) = image::image_dimensions(texture)
+        {
+            warn!("cannot load texture {}: {err}", texture.display());
+        }
+
+        if mesh.point_cloud {
+            println!("{}: {} points", path.display(), mesh.vertex_count());
+        } else {
+            println!(
+                "{}: {} vertices, {} triangles",
+                path.display(),
+                mesh.vertex_count(),
+                mesh.triangle_count()
+            );
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} of {} models failed to load", paths.len());
+    }
+
+    Ok(())
+}
+
+/// Axis-aligned view bound to a number key, as its name and the azimuth and
+/// elevation of the camera. The front view looks along -Z.
+fn preset_view(keycode: Keycode) -> Option<(&'static str, f32, f32)> {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    match keycode {
+        Keycode::_1 | Keycode::Kp1 => Some(("front", FRAC_PI_2, 0.0)),
+        Keycode::_2 | Keycode::Kp2 => Some(("back", -FRAC_PI_2, 0.0)),
+        Keycode::_3 | Keycode::Kp3 => Some(("left", PI, 0.0)),
+        Keycode::_4 | Keycode::Kp4 => Some(("right", 0.0, 0.0)),
+        Keycode::_5 | Keycode::Kp5 => Some(("top", FRAC_PI_2, POLE_ELEVATION)),
+        Keycode::_6 | Keycode::Kp6 => Some(("bottom", FRAC_PI_2, -POLE_ELEVATION)),
+        _ => None,
+    }
+}
+
+/// Turns a unit direction by an azimuth around the Y axis and an elevation,
+/// keeping it away from the poles so that it can keep turning.
+fn orbit_direction(direction: Vec3, delta_azimuth: f32, delta_elevation: f32) -> Vec3 {
+    let max_elevation = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    let azimuth = direction.z.atan2(direction.x) + delta_azimuth;
+    let elevation = (direction.y.clamp(-1.0, 1.0).asin() + delta_elevation)
+        .clamp(-max_elevation, max_elevation);
+
+    Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.sin(),
+        elevation.cos() * azimuth.sin(),
+    )
+}
+
+/// Places the model in the world.
+///
+/// When normalizing, the model is centered at the origin and fitted in a unit
+/// cube. Either way, `rotation` and `scale` apply around its center so that it
+/// stays in view.
+fn model_matrix(bounds: &BoundingBox, scale: f32, normalize: bool, rotation: Quat) -> Mat4 {
+    let center = bounds.center();
+    let rotated = bounds.transformed(Mat4::from_quat(rotation));
+    let extent = (rotated.max - rotated.min).max_element();
+
+    let (translation, scale) = if normalize && extent > 0.0 {
+        (Vec3::ZERO, scale / extent)
+    } else {
+        (center, scale)
+    };
+
+    Mat4::from_translation(translation)
+        * Mat4::from_scale(Vec3::splat(scale))
+        * Mat4::from_quat(rotation)
+        * Mat4::from_translation(-center)
+}
+
+/// Places the models side by side along X, in order, so that they don't
+/// overlap. The first model keeps its own placement.
+fn layout_models(models: &mut [Model], scale: f32, normalize: bool, rotation: Quat) {
+    let mut next_min_x = None;
+
+    for model in models {
+        model.transform = model_matrix(&model.bounds, scale, normalize, rotation);
+
+        let placed = model.bounds.transformed(model.transform);
+        let offset = next_min_x.map_or(0.0, |min_x| min_x - placed.min.x);
+        model.transform = Mat4::from_translation(Vec3::X * offset) * model.transform;
+
+        let gap = placed.radius() * MODEL_SPACING;
+        next_min_x = Some(placed.max.x + offset + gap);
+    }
+}
+
+/// Starts watching a model for hot reload, which is best effort: failures
+/// are reported and leave the viewer without reloading.
+fn watch_model(path: &Path) -> Option<FileWatcher> {
+    // The standard input can't change once read.
+    if path == Path::new("-") {
+        return None;
+    }
+
+    match FileWatcher::new(path) {
+        Ok(file_watcher) => Some(file_watcher),
+        Err(err) => {
+            warn!("hot reload disabled: {err:#}");
+            None
+        }
+    }
+}
+
+/// Mesh data laid out for the GPU: indexed triangles with interleaved
+/// attributes, colored normal lines and the bounding box. Edges are only
+/// extracted once shown, see [`Model::upload_edges`].
+///
+/// The triangles come twice, once with smooth normals and once with face
+/// normals for flat shading.
+struct MeshGeometry {
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    flat_vertices: Vec<f32>,
+    flat_indices: Vec<u32>,
+    normal_lines: Vec<f32>,
+    bounding_box_lines: Vec<f32>,
+}
+
+impl MeshGeometry {
+    fn new(mesh: &Mesh, crease_angle: f32) -> Self {
+        let (vertices, indices) = build_indexed_mesh(
+            &mesh.positions,
+            &mesh.vertex_normals(crease_angle),
+            &mesh.colors,
+            &mesh.tex_coords,
+        );
+        let (flat_vertices, flat_indices) = build_indexed_mesh(
+            &mesh.positions,
+            &mesh.face_normals(),
+            &mesh.colors,
+            &mesh.tex_coords,
+        );
+        let normal_lines = build_normal_lines(&vertices, mesh.bounds.radius() * NORMAL_LINE_LENGTH);
+        let bounding_box_lines = build_bounding_box_lines(&mesh.bounds);
+
+        Self {
+            vertices,
+            indices,
+            flat_vertices,
+            flat_indices,
+            normal_lines,
+            bounding_box_lines,
+        }
+    }
+
+    /// Hash of everything uploaded for the mesh, along with its texture.
+    fn content_hash(&self, texture: Option<&Path>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        // The other buffers derive from the triangles.
+        bytemuck::cast_slice::<f32, u8>(&self.vertices).hash(&mut hasher);
+        self.indices.hash(&mut hasher);
+        texture.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+/// GPU buffers of an uploaded [`MeshGeometry`].
+struct GpuMesh<'gl> {
+    triangles: VertexArray<'gl>,
+    index_count: i32,
+    flat_triangles: VertexArray<'gl>,
+    flat_index_count: i32,
+    /// Missing until the edges are first shown.
+    edges: Option<VertexArray<'gl>>,
+    edge_vertex_count: i32,
+    normals: VertexArray<'gl>,
+    normal_vertex_count: i32,
+    bounding_box: VertexArray<'gl>,
+}
+
+impl<'gl> GpuMesh<'gl> {
+    /// Triangles with smooth or flat normals, along with their index count.
+    fn triangles(&self, flat: bool) -> (&VertexArray<'gl>, i32) {
+        if flat {
+            (&self.flat_triangles, self.flat_index_count)
+        } else {
+            (&self.triangles, self.index_count)
+        }
+    }
+
+    fn upload(gl: &'gl glow::Context, geometry: &MeshGeometry) -> color_eyre::Result<Self> {
+        debug!(
+            vertices = geometry.vertices.len() / VERTEX_SIZE,
+            indices = geometry.indices.len(),
+            "uploading mesh buffers"
+        );
+
+        Ok(Self {
+            triangles: create_obj_buffers(gl, &geometry.vertices, &geometry.indices)?,
+            index_count: geometry.indices.len() as i32,
+            flat_triangles: create_obj_buffers(
+                gl,
+                &geometry.flat_vertices,
+                &geometry.flat_indices,
+            )?,
+            flat_index_count: geometry.flat_indices.len() as i32,
+            edges: None,
+            edge_vertex_count: 0,
+            normals: create_line_buffers(gl, &geometry.normal_lines, true)?,
+            normal_vertex_count: (geometry.normal_lines.len() / 6) as i32,
+            bounding_box: create_line_buffers(gl, &geometry.bounding_box_lines, true)?,
+        })
+    }
+}
+
+/// Frame times measured by `--bench`.
+struct Benchmark {
+    start: Instant,
+    duration: Duration,
+    frame_times: Vec<Duration>,
+}
+
+impl Benchmark {
+    fn new(duration: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            frame_times: Vec::new(),
+        }
+    }
+
+    fn report(&self) {
+        let mut frame_times = self
+            .frame_times
+            .iter()
+            .map(|frame_time| frame_time.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        frame_times.sort_by(f64::total_cmp);
+
+        let (Some(&min), Some(&max)) = (frame_times.first(), frame_times.last()) else {
+            println!("no frame rendered");
+            return;
+        };
+
+        let mean = frame_times.iter().sum::<f64>() / frame_times.len() as f64;
+        let p99_index = (frame_times.len() as f64 * 0.99).ceil() as usize - 1;
+        let p99 = frame_times[p99_index];
+
+        println!("frames: {}", frame_times.len());
+        println!(
+            "frame time: min {min:.3} ms, max {max:.3} ms, mean {mean:.3} ms, p99 {p99:.3} ms"
+        );
+    }
+}
+
+/// A model loaded from a file and placed in the scene.
+struct Model<'gl> {
+    path: PathBuf,
+    bounds: BoundingBox,
+    /// Triangle soup kept around for picking.
+    positions: Vec<f32>,
+    gpu_mesh: GpuMesh<'gl>,
+    /// Hash of the uploaded geometry, to skip reloads that change nothing.
+    geometry_hash: u64,
+    /// Diffuse texture, missing if the mesh has none or it failed to load.
+    texture: Option<Texture<'gl>>,
+    /// Opacity of its most transparent material, telling whether some faces
+    /// get blended with `--transparency`.
+    opacity: f32,
+    /// Whether `positions` holds points rather than triangles.
+    point_cloud: bool,
+    /// Triangle selected by clicking on it.
+    highlight: Option<VertexArray<'gl>>,
+    file_watcher: Option<FileWatcher>,
+    /// Model matrix, set by [`layout_models`].
+    transform: Mat4,
+    /// Directory the model was opened from, to step through its files.
+    directory: Option<Directory>,
+}
+
+/// Model files of a directory, one of which is shown.
+struct Directory {
+    files: Vec<PathBuf>,
+    index: usize,
+}
+
+impl<'gl> Model<'gl> {
+    fn new(
+        gl: &'gl glow::Context,
+        path: PathBuf,
+        mesh: Mesh,
+        geometry: &MeshGeometry,
+    ) -> color_eyre::Result<Self> {
+        Ok(Self {
+            gpu_mesh: GpuMesh::upload(gl, geometry)?,
+            geometry_hash: geometry.content_hash(mesh.texture.as_deref()),
+            opacity: mesh.opacity,
+            point_cloud: mesh.point_cloud,
+            texture: mesh
+                .texture
+                .as_deref()
+                .and_then(|path| load_texture(gl, path)),
+            file_watcher: watch_model(&path),
+            path,
+            bounds: mesh.bounds,
+            positions: mesh.positions,
+            highlight: None,
+            transform: Mat4::IDENTITY,
+            directory: None,
+        })
+    }
+
+    /// Extracts and uploads the edges if they aren't yet, which meshes only
+    /// need once edges are shown. Point clouds have none.
+    fn upload_edges(&mut self, gl: &'gl glow::Context) -> color_eyre::Result<()> {
+        if self.gpu_mesh.edges.is_some() || self.point_cloud {
+            return Ok(());
+        }
+
+        let edges = mesh::extract_edges_from_triangles(&self.positions);
+        debug!(edges = edges.len() / 6, "uploading edge buffers");
+
+        self.gpu_mesh.edges = Some(create_line_buffers(gl, &edges, false)?);
+        self.gpu_mesh.edge_vertex_count = (edges.len() / 3) as i32;
+
+        Ok(())
+    }
+
+    /// Loads a model file straight to the GPU.
+    fn load(
+        gl: &'gl glow::Context,
+        path: PathBuf,
+        config: &LoadConfig,
+    ) -> color_eyre::Result<Self> {
+        let (mesh, geometry) = load_mesh(&path, config)?;
+
+        Self::new(gl, path, mesh, &geometry)
+    }
+
+    /// Loads the file again, keeping the model untouched on failure.
+    ///
+    /// Returns whether anything changed: editors often save or touch files
+    /// without modifying them, which then skips the upload.
+    fn reload(&mut self, gl: &'gl glow::Context, config: &LoadConfig) -> color_eyre::Result<bool> {
+        let (mesh, geometry) = load_mesh(&self.path, config)?;
+
+        let geometry_hash = geometry.content_hash(mesh.texture.as_deref());
+        if geometry_hash == self.geometry_hash {
+            debug!("{} is unchanged, skipping upload", self.path.display());
+            return Ok(false);
+        }
+
+        self.gpu_mesh = GpuMesh::upload(gl, &geometry)?;
+        self.geometry_hash = geometry_hash;
+        self.texture = mesh
+            .texture
+            .as_deref()
+            .and_then(|path| load_texture(gl, path));
+        self.opacity = mesh.opacity;
+        self.point_cloud = mesh.point_cloud;
+        self.bounds = mesh.bounds;
+        self.positions = mesh.positions;
+        self.highlight = None;
+
+        Ok(true)
+    }
+
+    /// Replaces the model with the file `step` places away in its directory,
+    /// wrapping around and skipping the files that fail to load.
+    ///
+    /// Returns whether another file could be loaded.
+    fn step_directory(&mut self, gl: &'gl glow::Context, step: isize, config: &LoadConfig) -> bool {
+        let Some(directory) = &self.directory else {
+            return false;
+        };
+
+        let count = directory.files.len();
+        let mut index = directory.index;
+
+        for _ in 1..count {
+            index = (index as isize + step).rem_euclid(count as isize) as usize;
+
+            match Self::load(gl, directory.files[index].clone(), config) {
+                Ok(mut model) => {
+                    model.directory = self
+                        .directory
+                        .take()
+                        .map(|directory| Directory { index, ..directory });
+                    *self = model;
+
+                    return true;
+                }
+                Err(err) => error!("{err:#}"),
+            }
+        }
+
+        false
+    }
+
+    fn triangle_count(&self) -> usize {
+        if self.point_cloud {
+            0
+        } else {
+            self.positions.len() / 9
+        }
+    }
+
+    fn point_count(&self) -> usize {
+        if self.point_cloud {
+            self.positions.len() / 3
+        } else {
+            0
+        }
+    }
+
+    /// Bounds of the model once placed in the scene.
+    fn framed_bounds(&self) -> BoundingBox {
+        self.bounds.transformed(self.transform)
+    }
+}
+
+/// Point of a model surface under the cursor.
+struct Pick {
+    model_index: usize,
+    triangle_index: usize,
+    point: Vec3,
+}
+
+/// Ray going into the scene through a point of the window, in pixels from its
+/// top left corner.
+fn cursor_ray(camera: &Camera, cursor: Vec2, viewport: Viewport, window_height: u32) -> Ray {
+    let view_projection = camera.projection_matrix(viewport.aspect()) * camera.view_matrix();
+
+    // The viewport starts from the bottom of the window, the cursor from the top.
+    let top = window_height as i32 - viewport.y - viewport.height as i32;
+    let cursor = cursor - Vec2::new(viewport.x as f32, top as f32);
+
+    Ray::from_cursor(
+        cursor,
+        Vec2::new(viewport.width as f32, viewport.height as f32),
+        view_projection,
+    )
+}
+
+/// Point that zooming with the wheel keeps under the cursor: the model surface
+/// there, or else the depth of the camera target.
+fn zoom_anchor(camera: &Camera, models: &[Model], ray: &Ray) -> Vec3 {
+    if let Some(pick) = pick_models(models, ray) {
+        return pick.point;
+    }
+
+    let target = camera.target();
+    let normal = target - camera.position();
+
+    ray.intersect_plane(target, normal)
+        .map_or(target, |distance| ray.at(distance))
+}
+
+/// Part of the window drawn to, in OpenGL window coordinates.
+#[derive(Debug, Clone, Copy)]
+struct Viewport {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Viewport {
+    /// The whole window, or the largest centered area of the given aspect
+    /// ratio, the rest being left to the background color.
+    fn fit(window_width: u32, window_height: u32, aspect: Option<f32>) -> Self {
+        let (width, height) = match aspect {
+            Some(aspect) if (window_width as f32) > window_height as f32 * aspect => (
+                (window_height as f32 * aspect).round() as u32,
+                window_height,
+            ),
+            Some(aspect) => (window_width, (window_width as f32 / aspect).round() as u32),
+            None => (window_width, window_height),
+        };
+        let (width, height) = (width.max(1), height.max(1));
+
+        Self {
+            x: (window_width.saturating_sub(width) / 2) as i32,
+            y: (window_height.saturating_sub(height) / 2) as i32,
+            width,
+            height,
+        }
+    }
+
+    fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    fn apply(&self, gl: &glow::Context) {
+        unsafe { gl.viewport(self.x, self.y, self.width as i32, self.height as i32) };
+    }
+}
+
+/// Nearest model surface hit by a ray in the scene.
+fn pick_models(models: &[Model], ray: &Ray) -> Option<Pick> {
+    models
+        .iter()
+        .enumerate()
+        .filter(|(_, model)| !model.point_cloud)
+        .filter_map(|(model_index, model)| {
+            // Affine transforms keep distances along the ray comparable.
+            let model_ray = ray.transformed(model.transform.inverse());
+            let (triangle_index, distance) = model_ray.intersect_triangles(&model.positions)?;
+
+            let pick = Pick {
+                model_index,
+                triangle_index,
+                point: ray.at(distance),
+            };
+
+            Some((pick, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(pick, _)| pick)
+}
+
+/// Rendering settings chosen at startup.
+struct RenderConfig {
+    background_color: Vec3,
+    /// Fill color replacing the colors of the meshes.
+    mesh_color: Option<Vec3>,
+    edge_width: f32,
+    axis_width: f32,
+    /// Whether the edges write depth, covering the surface behind them.
+    edge_depth_write: bool,
+    /// Multiplier for the polygon offset applied to the surface when edges are
+    /// drawn over it.
+    edge_offset: f32,
+    /// Image of the matcap shading mode.
+    matcap: Option<PathBuf>,
+    /// Thickness of the outline, relative to the model radius.
+    outline_width: f32,
+    /// Whether the opacity of the materials is honored.
+    transparency: bool,
+    /// Diameter in pixels of the points of point clouds and the points mode.
+    point_size: f32,
+    /// Sources of the OBJ program read instead of the embedded ones.
+    obj_vertex_shader: Option<PathBuf>,
+    obj_fragment_shader: Option<PathBuf>,
+}
+
+/// How model files are turned into meshes, kept for reloads.
+struct LoadConfig {
+    /// Angle in degrees above which computed normals aren't smoothed.
+    crease_angle: f32,
+    /// Triangle budget of the preview subsampling, if enabled.
+    max_triangles: Option<usize>,
+}
+
+/// Input settings chosen at startup.
+struct InputConfig {
+    /// Multiplier for mouse and arrow key orbiting.
+    orbit_sensitivity: f32,
+    /// Multiplier for wheel and keyboard zooming.
+    zoom_sensitivity: f32,
+}
+
+/// Display settings toggled from the keyboard.
+struct ViewOptions {
+    shading: Shading,
+    render_mode: RenderMode,
+    /// Colors every triangle differently to show the topology.
+    random_colors: bool,
+    /// Lights back faces too, which open meshes need.
+    double_sided: bool,
+    /// Negates the normals, for meshes exported inside out.
+    invert_normals: bool,
+    flat_shading: bool,
+    show_edges: bool,
+    show_normals: bool,
+    show_axes: bool,
+    show_grid: bool,
+    show_crosshair: bool,
+    show_bounding_box: bool,
+    /// Dark rim around the silhouette in solid mode.
+    show_outline: bool,
+    light_direction: Vec3,
+}
+
+impl Default for ViewOptions {
+    fn default() -> Self {
+        Self {
+            shading: Shading::Phong,
+            render_mode: RenderMode::Solid,
+            random_colors: false,
+            double_sided: true,
+            invert_normals: false,
+            flat_shading: false,
+            show_edges: true,
+            show_normals: false,
+            show_axes: true,
+            show_grid: true,
+            show_crosshair: false,
+            show_bounding_box: false,
+            show_outline: false,
+            light_direction: LIGHT_DIRECTION.normalize(),
+        }
+    }
+}
+
+/// Shader programs and static buffers used to draw a frame.
+struct Renderer<'gl> {
+    gl: &'gl glow::Context,
+    obj_program: ShaderProgram<'gl>,
+    /// Thick line programs, with a fixed color for edges and vertex colors
+    /// for the axes and every other line.
+    edges_program: ShaderProgram<'gl>,
+    axis_program: ShaderProgram<'gl>,
+    highlight_program: ShaderProgram<'gl>,
+    crosshair_program: ShaderProgram<'gl>,
+    outline_program: ShaderProgram<'gl>,
+    obj_locations: ObjUniformLocations,
+    edge_locations: LineUniformLocations,
+    axis_locations: LineUniformLocations,
+    crosshair_scale_location: Option<glow::UniformLocation>,
+    outline_offset_location: Option<glow::UniformLocation>,
+    /// Missing until the axes are first shown.
+    axis_buffers: Option<VertexArray<'gl>>,
+    grid_buffers: VertexArray<'gl>,
+    crosshair_buffers: VertexArray<'gl>,
+    /// Uniform buffer backing the `Matrices` block of every program.
+    matrices: Buffer<'gl>,
+    /// Missing without `--matcap` or when its image failed to load.
+    matcap: Option<Texture<'gl>>,
+    config: RenderConfig,
+}
+
+impl<'gl> Renderer<'gl> {
+    fn new(gl: &'gl glow::Context, config: RenderConfig) -> color_eyre::Result<Self> {
+        // A broken custom shader can still be fixed and reloaded with F5.
+        let obj_program = create_obj_program(gl, &config).or_else(|err| {
+            error!("cannot build shaders, using the embedded ones: {err:?}");
+            create_shader_program(gl, OBJ_VERTEX_SHADER_SOURCE, OBJ_FRAGMENT_SHADER_SOURCE)
+        })?;
+        let edges_program =
+            create_shader_program(gl, LINE_VERTEX_SHADER_SOURCE, EDGE_FRAGMENT_SHADER_SOURCE)?;
+        let axis_program =
+            create_shader_program(gl, LINE_VERTEX_SHADER_SOURCE, LINE_FRAGMENT_SHADER_SOURCE)?;
+        let crosshair_program = create_shader_program(
+            gl,
+            CROSSHAIR_VERTEX_SHADER_SOURCE,
+            CROSSHAIR_FRAGMENT_SHADER_SOURCE,
+        )?;
+        let outline_program = create_shader_program(
+            gl,
+            OUTLINE_VERTEX_SHADER_SOURCE,
+            OUTLINE_FRAGMENT_SHADER_SOURCE,
+        )?;
+
+        Ok(Self {
+            gl,
+            obj_locations: ObjUniformLocations::new(gl, obj_program.program),
+            edge_locations: LineUniformLocations::new(gl, edges_program.program),
+            axis_locations: LineUniformLocations::new(gl, axis_program.program),
+            crosshair_scale_location: uniform_location(gl, crosshair_program.program, "scale"),
+            outline_offset_location: uniform_location(
+                gl,
+                outline_program.program,
+                "outline_offset",
+            ),
+            obj_program,
+            edges_program,
+            axis_program,
+            highlight_program: create_shader_program(
+                gl,
+                COLORED_VERTEX_SHADER_SOURCE,
+                COLORED_FRAGMENT_SHADER_SOURCE,
+            )?,
+            crosshair_program,
+            outline_program,
+            axis_buffers: None,
+            grid_buffers: create_line_buffers(gl, &build_grid_lines(GRID_CELLS), true)?,
+            crosshair_buffers: create_position_buffers(gl, &CROSSHAIR_DATA)?,
+            matrices: create_matrices_buffer(gl)?,
+            matcap: config
+                .matcap
+                .as_deref()
+                .and_then(|path| load_texture(gl, path)),
+            config,
+        })
+    }
+
+    /// Uploads the axes if they aren't yet, which only happens once they are
+    /// shown.
+    fn upload_axes(&mut self) -> color_eyre::Result<()> {
+        if self.axis_buffers.is_none() {
+            self.axis_buffers = Some(create_line_buffers(self.gl, &AXIS_DATA, true)?);
+        }
+
+        Ok(())
+    }
+
+    /// Builds the OBJ program again from its files, keeping the current one
+    /// if that fails.
+    fn reload_shaders(&mut self) -> color_eyre::Result<()> {
+        let program = create_obj_program(self.gl, &self.config)?;
+
+        self.obj_locations = ObjUniformLocations::new(self.gl, program.program);
+        self.obj_program = program;
+
+        Ok(())
+    }
+
+    /// Whether the matcap shading mode can be used, which needs `--matcap` to
+    /// name an image that loaded.
+    fn has_matcap(&self) -> bool {
+        self.matcap.is_some()
+    }
+
+    /// Updates the matrices seen by the next draw calls, whatever the program.
+    fn set_matrices(&self, mvp: &Mat4, model: &Mat4) {
+        let matrices = [mvp.to_cols_array(), model.to_cols_array()];
+
+        unsafe {
+            self.gl
+                .bind_buffer(glow::UNIFORM_BUFFER, Some(self.matrices.buffer));
+            self.gl.buffer_sub_data_u8_slice(
+                glow::UNIFORM_BUFFER,
+                0,
+                bytemuck::cast_slice(&matrices),
+            );
+        }
+    }
+
+    /// Clears the current framebuffer and draws the models seen from `camera`.
+    ///
+    /// Returns the number of models skipped for being out of view.
+    fn draw(
+        &self,
+        models: &[Model],
+        options: &ViewOptions,
+        camera: &Camera,
+        aspect: f32,
+    ) -> color_eyre::Result<usize> {
+        let gl = self.gl;
+
+        let [r, g, b] = self.config.background_color.to_array();
+        unsafe { gl.clear_color(r, g, b, 1.0) };
+        unsafe { gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT) };
+
+        let view = camera.view_matrix();
+        let projection = camera.projection_matrix(aspect);
+        let view_projection = projection * view;
+
+        let mut viewport = [0; 4];
+        unsafe { gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport) };
+        let viewport_size = Vec2::new(viewport[2] as f32, viewport[3] as f32);
+
+        if options.show_grid {
+            let grid = grid_transform(models);
+            self.set_matrices(&(view_projection * grid), &grid);
+
+            draw_lines(
+                gl,
+                self.grid_buffers.vao,
+                self.axis_program.program,
+                &self.axis_locations,
+                ((GRID_CELLS + 1) * 4) as i32,
+                1.0,
+                viewport_size,
+            )?;
+        }
+
+        let frustum = Frustum::from_matrix(view_projection);
+        let mut culled_models = 0;
+
+        let mut visible_models = Vec::with_capacity(models.len());
+
+        for model in models {
+            let bounds = model.framed_bounds();
+
+            if !frustum.intersects_sphere(bounds.center(), bounds.radius()) {
+                culled_models += 1;
+                continue;
+            }
+
+            visible_models.push(model);
+        }
+
+        for &model in &visible_models {
+            self.draw_model(model, options, camera, &view_projection, viewport_size)?;
+        }
+
+        // See-through faces come once everything opaque is drawn, from the
+        // farthest model to the nearest so that each blends over what is
+        // behind it.
+        let eye = camera.position();
+        let distance = |model: &Model| model.framed_bounds().center().distance_squared(eye);
+
+        let mut translucent_models = visible_models
+            .into_iter()
+            .filter(|model| self.is_translucent(model, options))
+            .collect::<Vec<_>>();
+        translucent_models.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+
+        for model in translucent_models {
+            self.draw_translucent(model, options, camera, &view_projection)?;
+        }
+
+        if options.show_axes
+            && let Some(axes) = &self.axis_buffers
+        {
+            unsafe { gl.disable(glow::DEPTH_TEST) };
+
+            // Axes ignore the model scale to remain a unit-length reference.
+            self.set_matrices(&view_projection, &Mat4::IDENTITY);
+
+            draw_lines(
+                gl,
+                axes.vao,
+                self.axis_program.program,
+                &self.axis_locations,
+                (AXIS_DATA.len() / 6) as i32,
+                self.config.axis_width,
+                viewport_size,
+            )?;
+
+            unsafe { gl.enable(glow::DEPTH_TEST) };
+        }
+
+        if options.show_crosshair {
+            unsafe { gl.disable(glow::DEPTH_TEST) };
+
+            draw_crosshair(
+                gl,
+                self.crosshair_buffers.vao,
+                self.crosshair_program.program,
+                self.crosshair_scale_location.as_ref(),
+                aspect,
+            )?;
+
+            unsafe { gl.enable(glow::DEPTH_TEST) };
+        }
+
+        Ok(culled_models)
+    }
+
+    /// Whether the model has see-through faces to blend, which only solid
+    /// rendering with `--transparency` does.
+    fn is_translucent(&self, model: &Model, options: &ViewOptions) -> bool {
+        self.config.transparency
+            && model.opacity < 1.0
+            && !model.point_cloud
+            && options.render_mode == RenderMode::Solid
+    }
+
+    /// Settings of the OBJ program for drawing the model.
+    fn obj_uniforms(
+        &self,
+        model: &Model,
+        options: &ViewOptions,
+        camera: &Camera,
+        alpha_pass: AlphaPass,
+    ) -> ObjUniforms {
+        let shading = if model.point_cloud {
+            Shading::Unlit
+        } else {
+            options.shading
+        };
+
+        ObjUniforms {
+            view_position: camera.position(),
+            light_direction: options.light_direction,
+            lighting_enabled: shading == Shading::Phong,
+            view: camera.view_matrix(),
+            matcap: self
+                .matcap
+                .as_ref()
+                .filter(|_| shading == Shading::Matcap)
+                .map(|matcap| matcap.texture),
+            random_colors: options.random_colors,
+            double_sided: options.double_sided,
+            invert_normals: options.invert_normals,
+            transparency: self.config.transparency,
+            alpha_pass,
+            texture: model.texture.as_ref().map(|texture| texture.texture),
+            fill_color: self.config.mesh_color,
+            point_size: self.config.point_size,
+        }
+    }
+
+    /// Blends the see-through faces of a model left out by [`Self::draw_model`].
+    fn draw_translucent(
+        &self,
+        model: &Model,
+        options: &ViewOptions,
+        camera: &Camera,
+        view_projection: &Mat4,
+    ) -> color_eyre::Result<()> {
+        self.set_matrices(&(*view_projection * model.transform), &model.transform);
+
+        let obj_uniforms = self.obj_uniforms(model, options, camera, AlphaPass::Translucent);
+        let (triangles, index_count) = model.gpu_mesh.triangles(options.flat_shading);
+
+        draw_transparent_obj(
+            self.gl,
+            triangles.vao,
+            self.obj_program.program,
+            &self.obj_locations,
+            &obj_uniforms,
+            index_count,
+        )
+    }
+
+    fn draw_model(
+        &self,
+        model: &Model,
+        options: &ViewOptions,
+        camera: &Camera,
+        view_projection: &Mat4,
+        viewport_size: Vec2,
+    ) -> color_eyre::Result<()> {
+        let gl = self.gl;
+
+        // Every part of the model below shares these matrices.
+        self.set_matrices(&(*view_projection * model.transform), &model.transform);
+
+        // Point clouds have neither faces to fill nor normals to shade with.
+        let render_mode = if model.point_cloud {
+            RenderMode::Points
+        } else {
+            options.render_mode
+        };
+
+        // The see-through faces wait for `draw_translucent`.
+        let alpha_pass = if self.is_translucent(model, options) {
+            AlphaPass::Opaque
+        } else {
+            AlphaPass::All
+        };
+
+        let obj_uniforms = self.obj_uniforms(model, options, camera, alpha_pass);
+        let (triangles, index_count) = model.gpu_mesh.triangles(options.flat_shading);
+
+        match render_mode {
+            RenderMode::Solid => {
+                if options.show_outline && self.config.outline_width > 0.0 {
+                    // Inverted normals would shrink the hull inside the mesh.
+                    let direction = if options.invert_normals { -1.0 } else { 1.0 };
+
+                    draw_outline(
+                        gl,
+                        model.gpu_mesh.triangles.vao,
+                        self.outline_program.program,
+                        self.outline_offset_location.as_ref(),
+                        direction * self.config.outline_width * model.bounds.radius(),
+                        model.gpu_mesh.index_count,
+                    )?;
+                }
+
+                // Push the surface back so that the edges drawn over it don't
+                // fight with it for depth.
+                let offset_surface = options.show_edges && self.config.edge_offset > 0.0;
+
+                if offset_surface {
+                    unsafe {
+                        gl.enable(glow::POLYGON_OFFSET_FILL);
+                        gl.polygon_offset(
+                            EDGE_OFFSET_FACTOR * self.config.edge_offset,
+                            EDGE_OFFSET_UNITS * self.config.edge_offset,
+                        );
+                    }
+                }
+
+                draw_obj(
+                    gl,
+                    triangles.vao,
+                    self.obj_program.program,
+                    &self.obj_locations,
+                    &obj_uniforms,
+                    glow::TRIANGLES,
+                    index_count,
+                )?;
+
+                if offset_surface {
+                    unsafe { gl.disable(glow::POLYGON_OFFSET_FILL) };
+                }
+
+                if options.show_edges
+                    && let Some(edges) = &model.gpu_mesh.edges
+                {
+                    unsafe { gl.depth_mask(self.config.edge_depth_write) };
+
+                    draw_lines(
+                        gl,
+                        edges.vao,
+                        self.edges_program.program,
+                        &self.edge_locations,
+                        model.gpu_mesh.edge_vertex_count,
+                        self.config.edge_width,
+                        viewport_size,
+                    )?;
+
+                    unsafe { gl.depth_mask(true) };
+                }
+            }
+            RenderMode::Wireframe => {
+                unsafe { gl.polygon_mode(glow::FRONT_AND_BACK, glow::LINE) };
+
+                draw_obj(
+                    gl,
+                    triangles.vao,
+                    self.obj_program.program,
+                    &self.obj_locations,
+                    &obj_uniforms,
+                    glow::TRIANGLES,
+                    index_count,
+                )?;
+
+                unsafe { gl.polygon_mode(glow::FRONT_AND_BACK, glow::FILL) };
+            }
+            RenderMode::Points => {
+                draw_obj(
+                    gl,
+                    triangles.vao,
+                    self.obj_program.program,
+                    &self.obj_locations,
+                    &obj_uniforms,
+                    glow::POINTS,
+                    index_count,
+                )?;
+            }
+        }
+
+        if let Some(highlight) = &model.highlight {
+            draw_highlight(gl, highlight.vao, self.highlight_program.program)?;
+        }
+
+        if options.show_normals {
+            draw_lines(
+                gl,
+                model.gpu_mesh.normals.vao,
+                self.axis_program.program,
+                &self.axis_locations,
+                model.gpu_mesh.normal_vertex_count,
+                1.0,
+                viewport_size,
+            )?;
+        }
+
+        // The box is in model space, so it follows the model scale.
+        if options.show_bounding_box {
+            draw_lines(
+                gl,
+                model.gpu_mesh.bounding_box.vao,
+                self.axis_program.program,
+                &self.axis_locations,
+                12 * 2,
+                1.0,
+                viewport_size,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fragments drawn by the OBJ program, split by opacity so that see-through
+/// faces can be blended after everything opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AlphaPass {
+    All = 0,
+    Opaque = 1,
+    Translucent = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shading {
+    Phong,
+    Unlit,
+    /// Lookup in the `--matcap` image, only available when one is loaded.
+    Matcap,
+}
+
+impl Shading {
+    fn next(self, has_matcap: bool) -> Self {
+        match self {
+            Shading::Phong => Shading::Unlit,
+            Shading::Unlit if has_matcap => Shading::Matcap,
+            Shading::Unlit | Shading::Matcap => Shading::Phong,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderMode {
+    Solid,
+    Wireframe,
+    Points,
+}
+
+impl RenderMode {
+    fn next(self) -> Self {
+        match self {
+            RenderMode::Solid => RenderMode::Wireframe,
+            RenderMode::Wireframe => RenderMode::Points,
+            RenderMode::Points => RenderMode::Solid,
+        }
+    }
+}
+
+/// Prints what is needed to diagnose platform-specific rendering issues.
+fn print_gl_info(video_subsystem: &sdl3::VideoSubsystem, gl: &glow::Context) {
+    let current_driver = video_subsystem.current_video_driver();
+    let drivers = sdl3::video::drivers()
+        .map(|driver| {
+            if driver == current_driver {
+                format!("{driver} (current)")
+            } else {
+                driver.to_owned()
+            }
+        })
+        .collect::<Vec<_>>();
 
-                        camera_theta += dx * 0.005;
-                        camera_phi += dy * 0.005;
+    let gl_attr = video_subsystem.gl_attr();
+    let (major, minor) = gl_attr.context_version();
 
-                        camera_phi = camera_phi
-                            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+    println!("sdl version: {}", sdl3::version::version());
+    println!("video drivers: {}", drivers.join(", "));
+    println!("context profile: {:?}", gl_attr.context_profile());
+    println!("context version: {major}.{minor}");
 
-                        mouse_last_x = x;
-                        mouse_last_y = y;
-                    }
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Left),
-                    ..
-                } => {
-                    camera_theta -= 0.1;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Right),
-                    ..
-                } => {
-                    camera_theta += 0.1;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Up),
-                    ..
-                } => {
-                    camera_phi += 0.1;
-                    if camera_phi > std::f32::consts::FRAC_PI_2 {
-                        camera_phi = std::f32::consts::FRAC_PI_2;
-                    }
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Down),
-                    ..
-                } => {
-                    camera_phi -= 0.1;
-                    if camera_phi < -std::f32::consts::FRAC_PI_2 {
-                        camera_phi = -std::f32::consts::FRAC_PI_2;
-                    }
-                }
-                Event::MouseWheel { y, .. } => {
-                    if y > 0.0 {
-                        camera_zoom_factor -= 0.1;
-                    } else {
-                        camera_zoom_factor += 0.1;
-                    }
-                    camera_zoom_factor = camera_zoom_factor.clamp(0.1, 10.0);
-                }
-                _ => {}
+    unsafe {
+        println!("opengl vendor: {}", gl.get_parameter_string(glow::VENDOR));
+        println!(
+            "opengl renderer: {}",
+            gl.get_parameter_string(glow::RENDERER)
+        );
+        println!("opengl version: {}", gl.get_parameter_string(glow::VERSION));
+        println!(
+            "glsl version: {}",
+            gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION)
+        );
+    }
+}
+
+/// Forwards driver debug messages to the log, skipping purely informational ones.
+fn enable_gl_debug_output(gl: &mut glow::Context) {
+    if !gl.supports_debug() {
+        warn!("the OpenGL context does not support debug output");
+        return;
+    }
+
+    unsafe {
+        gl.enable(glow::DEBUG_OUTPUT);
+        gl.enable(glow::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl.debug_message_callback(|_source, kind, id, severity, message| {
+            let severity = match severity {
+                glow::DEBUG_SEVERITY_HIGH => "high",
+                glow::DEBUG_SEVERITY_MEDIUM => "medium",
+                glow::DEBUG_SEVERITY_LOW => "low",
+                _ => return,
+            };
+
+            let kind = match kind {
+                glow::DEBUG_TYPE_ERROR => "error",
+                glow::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated",
+                glow::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+                glow::DEBUG_TYPE_PORTABILITY => "portability",
+                glow::DEBUG_TYPE_PERFORMANCE => "performance",
+                _ => "other",
             };
-        }
 
-        let camera_radius = INITIAL_CAMERA_RADIUS * camera_zoom_factor;
+            warn!("gl {kind} ({severity}, id {id}): {message}");
+        });
+    }
+}
 
-        let x = camera_radius * camera_phi.cos() * camera_theta.cos();
-        let y = camera_radius * camera_phi.sin();
-        let z = camera_radius * camera_phi.cos() * camera_theta.sin();
+fn screenshot_path() -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
 
-        let camera_position = Vec3::new(x, y, z);
+    PathBuf::from(format!("screenshot-{timestamp}.png"))
+}
 
-        unsafe { gl.clear_color(0.5, 0.5, 0.5, 1.0) };
-        unsafe { gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT) };
+/// Reads back the current color buffer and writes it to `path` as a PNG.
+fn save_screenshot(
+    gl: &glow::Context,
+    width: u32,
+    height: u32,
+    path: &Path,
+) -> color_eyre::Result<()> {
+    let mut pixels = vec![0u8; width as usize * height as usize * 3];
 
-        let model = Mat4::IDENTITY;
-        let view = Mat4::look_at_rh(camera_position, camera_target, camera_up);
-        let projection = Mat4::perspective_rh_gl(
-            FOV.to_radians(),
-            WINDOW_WIDTH as f32 / WINDOW_HEIGHT as f32,
-            0.1,
-            100.0,
+    unsafe {
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            glow::RGB,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(Some(&mut pixels)),
         );
-        let mvp = projection * view * model;
+    }
 
-        draw_obj(
-            &gl,
-            obj_vao,
-            obj_program,
-            &mvp,
-            (vertex_data.len() / 3) as i32,
-        )?;
+    let mut image = image::RgbImage::from_raw(width, height, pixels)
+        .wrap_err("screenshot buffer does not match the window size")?;
 
-        draw_edges(&gl, edges_vao, edges_program, &mvp, edge_data.len() as i32)?;
+    // OpenGL rows start at the bottom of the framebuffer.
+    image::imageops::flip_vertical_in_place(&mut image);
 
-        unsafe { gl.disable(glow::DEPTH_TEST) };
+    image
+        .save(path)
+        .wrap_err_with(|| format!("cannot write screenshot {}", path.display()))
+}
 
-        draw_axes(&gl, axis_vao, axis_program, &mvp)?;
+/// Renders a single frame into an offscreen framebuffer and saves it as an
+/// image.
+fn render_to_file(
+    gl: &glow::Context,
+    width: u32,
+    height: u32,
+    viewport: Viewport,
+    path: &Path,
+    draw: impl FnOnce() -> color_eyre::Result<()>,
+) -> color_eyre::Result<()> {
+    unsafe {
+        let framebuffer = gl.create_framebuffer().wrap_gl_error()?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+
+        let color_buffer = gl.create_renderbuffer().wrap_gl_error()?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(color_buffer));
+        gl.renderbuffer_storage(
+            glow::RENDERBUFFER,
+            glow::SRGB8_ALPHA8,
+            width as i32,
+            height as i32,
+        );
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::RENDERBUFFER,
+            Some(color_buffer),
+        );
 
-        unsafe { gl.enable(glow::DEPTH_TEST) };
+        let depth_buffer = gl.create_renderbuffer().wrap_gl_error()?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(depth_buffer));
+        gl.renderbuffer_storage(
+            glow::RENDERBUFFER,
+            glow::DEPTH_COMPONENT24,
+            width as i32,
+            height as i32,
+        );
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::DEPTH_ATTACHMENT,
+            glow::RENDERBUFFER,
+            Some(depth_buffer),
+        );
 
-        window.gl_swap_window();
-    }
+        let result = if gl.check_framebuffer_status(glow::FRAMEBUFFER) == glow::FRAMEBUFFER_COMPLETE
+        {
+            viewport.apply(gl);
+            draw().and_then(|()| save_screenshot(gl, width, height, path))
+        } else {
+            Err(eyre!("offscreen framebuffer is incomplete"))
+        };
 
-    Ok(())
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.delete_renderbuffer(depth_buffer);
+        gl.delete_renderbuffer(color_buffer);
+        gl.delete_framebuffer(framebuffer);
+
+        result
+    }
 }
 
-fn extract_edges_from_triangles(vertex_data: &[f32]) -> Vec<f32> {
-    let mut edge_data = Vec::new();
+/// Collapses identical vertices into a unique interleaved vertex list and the
+/// `u32` indices referencing it, three per triangle.
+fn build_indexed_mesh(
+    vertex_data: &[f32],
+    normal_data: &[f32],
+    color_data: &[f32],
+    tex_coord_data: &[f32],
+) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(vertex_data.len() / 3);
+    let mut unique = HashMap::<[u32; VERTEX_SIZE], u32>::new();
+
+    let attributes = vertex_data
+        .chunks_exact(3)
+        .zip(normal_data.chunks_exact(3))
+        .zip(color_data.chunks_exact(4))
+        .zip(tex_coord_data.chunks_exact(3));
+
+    for (((position, normal), color), tex_coord) in attributes {
+        let mut vertex = [0.0f32; VERTEX_SIZE];
+        vertex[0..3].copy_from_slice(position);
+        vertex[3..6].copy_from_slice(normal);
+        vertex[6..10].copy_from_slice(color);
+        vertex[10..13].copy_from_slice(tex_coord);
+
+        let index = *unique.entry(vertex.map(f32::to_bits)).or_insert_with(|| {
+            vertices.extend_from_slice(&vertex);
+            (vertices.len() / VERTEX_SIZE - 1) as u32
+        });
+
+        indices.push(index);
+    }
 
-    for triangle in vertex_data.chunks_exact(9) {
-        let v0 = &triangle[0..3];
-        let v1 = &triangle[3..6];
-        let v2 = &triangle[6..9];
+    (vertices, indices)
+}
 
-        edge_data.extend_from_slice(v0);
-        edge_data.extend_from_slice(v1);
+/// Colored line-list vertices going out of each interleaved vertex along its
+/// normal, colored by the normal direction.
+fn build_normal_lines(vertices: &[f32], length: f32) -> Vec<f32> {
+    vertices
+        .chunks_exact(VERTEX_SIZE)
+        .flat_map(|vertex| {
+            let position = Vec3::from_slice(&vertex[0..3]);
+            let normal = Vec3::from_slice(&vertex[3..6]);
+            let color = normal * 0.5 + 0.5;
+
+            [position, color, position + normal * length, color].map(|point| point.to_array())
+        })
+        .flatten()
+        .collect()
+}
 
-        edge_data.extend_from_slice(v1);
-        edge_data.extend_from_slice(v2);
+/// Builds the OBJ program from the `--obj-vert` and `--obj-frag` files, using
+/// the embedded source of each one not given.
+fn create_obj_program<'gl>(
+    gl: &'gl glow::Context,
+    config: &RenderConfig,
+) -> color_eyre::Result<ShaderProgram<'gl>> {
+    let source = |path: &Option<PathBuf>, embedded: &str| match path {
+        Some(path) => std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("cannot read shader {}", path.display())),
+        None => Ok(embedded.to_owned()),
+    };
 
-        edge_data.extend_from_slice(v2);
-        edge_data.extend_from_slice(v0);
-    }
+    let vertex_source = source(&config.obj_vertex_shader, OBJ_VERTEX_SHADER_SOURCE)?;
+    let fragment_source = source(&config.obj_fragment_shader, OBJ_FRAGMENT_SHADER_SOURCE)?;
 
-    edge_data
+    create_shader_program(gl, &vertex_source, &fragment_source)
 }
 
-fn create_shader_program(
-    gl: &glow::Context,
+fn create_shader_program<'gl>(
+    gl: &'gl glow::Context,
     vertex_shader_source: &str,
     fragment_shader_source: &str,
-) -> color_eyre::Result<glow::Program> {
+) -> color_eyre::Result<ShaderProgram<'gl>> {
     unsafe {
         let vertex_shader = gl.create_shader(glow::VERTEX_SHADER).wrap_gl_error()?;
         gl.shader_source(vertex_shader, vertex_shader_source);
         gl.compile_shader(vertex_shader);
 
         if !gl.get_shader_compile_status(vertex_shader) {
-            bail!(
-                "vertex shader failed to compile: {}",
-                gl.get_shader_info_log(vertex_shader)
-            );
+            let info_log = gl.get_shader_info_log(vertex_shader);
+            gl.delete_shader(vertex_shader);
+
+            bail!("vertex shader failed to compile: {info_log}");
         }
 
         let fragment_shader = gl.create_shader(glow::FRAGMENT_SHADER).wrap_gl_error()?;
@@ -317,31 +3003,60 @@ fn create_shader_program(
         gl.compile_shader(fragment_shader);
 
         if !gl.get_shader_compile_status(fragment_shader) {
-            bail!(
-                "fragment shader failed to compile: {}",
-                gl.get_shader_info_log(fragment_shader)
-            );
+            let info_log = gl.get_shader_info_log(fragment_shader);
+            gl.delete_shader(vertex_shader);
+            gl.delete_shader(fragment_shader);
+
+            bail!("fragment shader failed to compile: {info_log}");
         }
 
         let program = gl.create_program().wrap_gl_error()?;
         gl.attach_shader(program, vertex_shader);
         gl.attach_shader(program, fragment_shader);
         gl.link_program(program);
+
+        gl.detach_shader(program, vertex_shader);
+        gl.detach_shader(program, fragment_shader);
+        gl.delete_shader(vertex_shader);
+        gl.delete_shader(fragment_shader);
+
         if !gl.get_program_link_status(program) {
-            bail!(
-                "program failed to link: {}",
-                gl.get_program_info_log(program)
-            );
+            let info_log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+
+            bail!("program failed to link: {info_log}");
+        }
+
+        if let Some(block_index) = gl.get_uniform_block_index(program, "Matrices") {
+            gl.uniform_block_binding(program, block_index, MATRICES_BINDING);
         }
 
-        Ok(program)
+        Ok(ShaderProgram::new(gl, program))
     }
 }
 
-fn create_obj_buffers(
-    gl: &glow::Context,
-    vertex_data: &[f32],
-) -> color_eyre::Result<(glow::NativeVertexArray, glow::NativeBuffer)> {
+/// Allocates the uniform buffer of the `Matrices` block, holding the mvp and
+/// model matrices, and attaches it to its binding point.
+fn create_matrices_buffer(gl: &glow::Context) -> color_eyre::Result<Buffer<'_>> {
+    unsafe {
+        let buffer = gl.create_buffer().wrap_gl_error()?;
+        gl.bind_buffer(glow::UNIFORM_BUFFER, Some(buffer));
+        gl.buffer_data_size(
+            glow::UNIFORM_BUFFER,
+            (2 * std::mem::size_of::<[f32; 16]>()) as i32,
+            glow::DYNAMIC_DRAW,
+        );
+        gl.bind_buffer_base(glow::UNIFORM_BUFFER, MATRICES_BINDING, Some(buffer));
+
+        Ok(Buffer::new(gl, buffer))
+    }
+}
+
+fn create_obj_buffers<'gl>(
+    gl: &'gl glow::Context,
+    vertices: &[f32],
+    indices: &[u32],
+) -> color_eyre::Result<VertexArray<'gl>> {
     unsafe {
         let vao = gl.create_vertex_array().wrap_gl_error()?;
         gl.bind_vertex_array(Some(vao));
@@ -350,28 +3065,159 @@ fn create_obj_buffers(
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
         gl.buffer_data_u8_slice(
             glow::ARRAY_BUFFER,
-            bytemuck::cast_slice(vertex_data),
+            bytemuck::cast_slice(vertices),
+            glow::STATIC_DRAW,
+        );
+
+        let ebo = gl.create_buffer().wrap_gl_error()?;
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+        gl.buffer_data_u8_slice(
+            glow::ELEMENT_ARRAY_BUFFER,
+            bytemuck::cast_slice(indices),
             glow::STATIC_DRAW,
         );
 
+        let stride = (VERTEX_SIZE * std::mem::size_of::<f32>()) as i32;
+
+        gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
         gl.vertex_attrib_pointer_f32(
-            0,
+            1,
             3,
             glow::FLOAT,
             false,
+            stride,
             3 * std::mem::size_of::<f32>() as i32,
-            0,
+        );
+        gl.vertex_attrib_pointer_f32(
+            2,
+            4,
+            glow::FLOAT,
+            false,
+            stride,
+            6 * std::mem::size_of::<f32>() as i32,
+        );
+        gl.vertex_attrib_pointer_f32(
+            3,
+            3,
+            glow::FLOAT,
+            false,
+            stride,
+            10 * std::mem::size_of::<f32>() as i32,
         );
         gl.enable_vertex_attrib_array(0);
+        gl.enable_vertex_attrib_array(1);
+        gl.enable_vertex_attrib_array(2);
+        gl.enable_vertex_attrib_array(3);
 
-        Ok((vao, vbo))
+        Ok(VertexArray::new(gl, vao, vec![vbo, ebo]))
     }
 }
 
-fn create_edge_buffers(
-    gl: &glow::Context,
-    edge_data: &[f32],
-) -> color_eyre::Result<(glow::NativeVertexArray, glow::NativeBuffer)> {
+/// Uploads an image file as a mipmapped sRGB texture, which is best effort:
+/// failures are reported and leave the mesh untextured, or the matcap mode
+/// unavailable.
+fn load_texture<'gl>(gl: &'gl glow::Context, path: &Path) -> Option<Texture<'gl>> {
+    let image = match image::open(path) {
+        Ok(image) => image,
+        Err(err) => {
+            warn!("cannot load texture {}: {err}", path.display());
+            return None;
+        }
+    };
+
+    // Images start at the top row, texture coordinates at the bottom one.
+    let image = image.flipv().into_rgba8();
+    let (width, height) = image.dimensions();
+
+    debug!(width, height, "uploading texture {}", path.display());
+
+    unsafe {
+        let texture = match gl.create_texture() {
+            Ok(texture) => texture,
+            Err(err) => {
+                warn!("cannot create texture: {err}");
+                return None;
+            }
+        };
+
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::SRGB8_ALPHA8 as i32,
+            width as i32,
+            height as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelUnpackData::Slice(Some(image.as_raw())),
+        );
+        gl.generate_mipmap(glow::TEXTURE_2D);
+
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR_MIPMAP_LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::REPEAT as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::REPEAT as i32);
+
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        Some(Texture::new(gl, texture))
+    }
+}
+
+/// Uploads a line list as one instance per segment, each instance reading
+/// both ends so that the line shader can expand it into a quad. Vertices hold
+/// a position, followed by a color when `colored`.
+fn create_line_buffers<'gl>(
+    gl: &'gl glow::Context,
+    line_data: &[f32],
+    colored: bool,
+) -> color_eyre::Result<VertexArray<'gl>> {
+    let vertex_floats = if colored { 6 } else { 3 };
+    let float_size = std::mem::size_of::<f32>() as i32;
+    let stride = 2 * vertex_floats * float_size;
+
+    unsafe {
+        let vao = gl.create_vertex_array().wrap_gl_error()?;
+        gl.bind_vertex_array(Some(vao));
+
+        let vbo = gl.create_buffer().wrap_gl_error()?;
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+        gl.buffer_data_u8_slice(
+            glow::ARRAY_BUFFER,
+            bytemuck::cast_slice(line_data),
+            glow::STATIC_DRAW,
+        );
+
+        let mut attributes = vec![(0, 0), (1, vertex_floats * float_size)];
+        if colored {
+            attributes.push((2, 3 * float_size));
+            attributes.push((3, (vertex_floats + 3) * float_size));
+        }
+
+        for (index, offset) in attributes {
+            gl.vertex_attrib_pointer_f32(index, 3, glow::FLOAT, false, stride, offset);
+            gl.vertex_attrib_divisor(index, 1);
+            gl.enable_vertex_attrib_array(index);
+        }
+
+        Ok(VertexArray::new(gl, vao, vec![vbo]))
+    }
+}
+
+/// Uploads plain positions, for lines drawn without the line shader.
+fn create_position_buffers<'gl>(
+    gl: &'gl glow::Context,
+    position_data: &[f32],
+) -> color_eyre::Result<VertexArray<'gl>> {
     unsafe {
         let vao = gl.create_vertex_array().wrap_gl_error()?;
         gl.bind_vertex_array(Some(vao));
@@ -380,7 +3226,7 @@ fn create_edge_buffers(
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
         gl.buffer_data_u8_slice(
             glow::ARRAY_BUFFER,
-            bytemuck::cast_slice(edge_data),
+            bytemuck::cast_slice(position_data),
             glow::STATIC_DRAW,
         );
 
@@ -394,7 +3240,7 @@ fn create_edge_buffers(
         );
         gl.enable_vertex_attrib_array(0);
 
-        Ok((vao, vbo))
+        Ok(VertexArray::new(gl, vao, vec![vbo]))
     }
 }
 
@@ -409,15 +3255,96 @@ const AXIS_DATA: [f32; 36] = [
     0.0, 0.0, 1.0, 0.0, 0.0, 1.0, // end point, color
 ];
 
-fn create_axis_buffer(
-    gl: &glow::Context,
-) -> color_eyre::Result<(glow::NativeVertexArray, glow::NativeBuffer)> {
+/// Arms of the crosshair, in normalized device coordinates before scaling.
+const CROSSHAIR_DATA: [f32; 12] = [
+    -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, // horizontal arm
+    0.0, -1.0, 0.0, 0.0, 1.0, 0.0, // vertical arm
+];
+
+/// Colored line-list vertices of a grid spanning -1 to 1 on the XZ plane.
+fn build_grid_lines(cells: usize) -> Vec<f32> {
+    (0..=cells)
+        .flat_map(|line| {
+            let offset = line as f32 / cells as f32 * 2.0 - 1.0;
+
+            [
+                Vec3::new(offset, 0.0, -1.0),
+                Vec3::new(offset, 0.0, 1.0),
+                Vec3::new(-1.0, 0.0, offset),
+                Vec3::new(1.0, 0.0, offset),
+            ]
+        })
+        .flat_map(|point| [point.to_array(), GRID_COLOR.to_array()])
+        .flatten()
+        .collect()
+}
+
+/// Colored line-list vertices for the twelve edges of a box.
+fn build_bounding_box_lines(bounds: &BoundingBox) -> Vec<f32> {
+    let corner = |index: usize| {
+        Vec3::select(
+            glam::BVec3::new(index & 1 != 0, index & 2 != 0, index & 4 != 0),
+            bounds.max,
+            bounds.min,
+        )
+    };
+
+    // Pairs of corners differing by a single bit, hence along a single axis.
+    (0..8)
+        .flat_map(|start| {
+            [1, 2, 4]
+                .into_iter()
+                .filter(move |bit| start & bit == 0)
+                .map(move |bit| (start, start | bit))
+        })
+        .flat_map(|(start, end)| [corner(start), corner(end)])
+        .flat_map(|point| [point.to_array(), BOUNDING_BOX_COLOR.to_array()])
+        .flatten()
+        .collect()
+}
+
+/// Places the unit grid under the models, wide enough to hold all of them.
+fn grid_transform(models: &[Model]) -> Mat4 {
+    let mut bounds = BoundingBox::empty();
+    for model in models {
+        let framed = model.framed_bounds();
+        bounds.extend(framed.min);
+        bounds.extend(framed.max);
+    }
+
+    let center = bounds.center();
+    let floor = Vec3::new(center.x, bounds.min.y, center.z);
+
+    Mat4::from_translation(floor) * Mat4::from_scale(Vec3::splat(bounds.radius()))
+}
+
+/// Uploads a single triangle of a triangle soup, in the highlight color.
+fn create_highlight_buffers<'gl>(
+    gl: &'gl glow::Context,
+    positions: &[f32],
+    triangle_index: usize,
+) -> color_eyre::Result<VertexArray<'gl>> {
+    let triangle = &positions[triangle_index * 9..][..9];
+    let vertices = triangle
+        .chunks_exact(3)
+        .flat_map(|position| [Vec3::from_slice(position), HIGHLIGHT_COLOR])
+        .flat_map(|vector| vector.to_array())
+        .collect::<Vec<_>>();
+
+    create_colored_buffers(gl, &vertices)
+}
+
+/// Uploads vertices interleaving a position and a color.
+fn create_colored_buffers<'gl>(
+    gl: &'gl glow::Context,
+    line_data: &[f32],
+) -> color_eyre::Result<VertexArray<'gl>> {
     unsafe {
         let vbo = gl.create_buffer().wrap_gl_error()?;
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
         gl.buffer_data_u8_slice(
             glow::ARRAY_BUFFER,
-            bytemuck::cast_slice(&AXIS_DATA),
+            bytemuck::cast_slice(line_data),
             glow::STATIC_DRAW,
         );
 
@@ -443,76 +3370,331 @@ fn create_axis_buffer(
         gl.enable_vertex_attrib_array(0);
         gl.enable_vertex_attrib_array(1);
 
-        Ok((vao, vbo))
+        Ok(VertexArray::new(gl, vao, vec![vbo]))
+    }
+}
+
+/// Uniform locations of a line program, looked up once after linking.
+struct LineUniformLocations {
+    line_width: Option<glow::UniformLocation>,
+    viewport_size: Option<glow::UniformLocation>,
+}
+
+impl LineUniformLocations {
+    fn new(gl: &glow::Context, program: glow::Program) -> Self {
+        Self {
+            line_width: uniform_location(gl, program, "line_width"),
+            viewport_size: uniform_location(gl, program, "viewport_size"),
+        }
+    }
+}
+
+/// Uniform locations of the OBJ program, looked up once after linking.
+struct ObjUniformLocations {
+    view_position: Option<glow::UniformLocation>,
+    light_direction: Option<glow::UniformLocation>,
+    lighting_enabled: Option<glow::UniformLocation>,
+    matcap: Option<glow::UniformLocation>,
+    matcap_map: Option<glow::UniformLocation>,
+    view: Option<glow::UniformLocation>,
+    random_colors: Option<glow::UniformLocation>,
+    double_sided: Option<glow::UniformLocation>,
+    invert_normals: Option<glow::UniformLocation>,
+    transparency: Option<glow::UniformLocation>,
+    alpha_pass: Option<glow::UniformLocation>,
+    textured: Option<glow::UniformLocation>,
+    diffuse_map: Option<glow::UniformLocation>,
+    fill_color: Option<glow::UniformLocation>,
+    use_fill_color: Option<glow::UniformLocation>,
+    point_size: Option<glow::UniformLocation>,
+}
+
+impl ObjUniformLocations {
+    fn new(gl: &glow::Context, program: glow::Program) -> Self {
+        let location = |name| uniform_location(gl, program, name);
+
+        Self {
+            view_position: location("view_position"),
+            light_direction: location("light_direction"),
+            lighting_enabled: location("lighting_enabled"),
+            matcap: location("matcap"),
+            matcap_map: location("matcap_map"),
+            view: location("view"),
+            random_colors: location("random_colors"),
+            double_sided: location("double_sided"),
+            invert_normals: location("invert_normals"),
+            transparency: location("transparency"),
+            alpha_pass: location("alpha_pass"),
+            textured: location("textured"),
+            diffuse_map: location("diffuse_map"),
+            fill_color: location("fill_color"),
+            use_fill_color: location("use_fill_color"),
+            point_size: location("point_size"),
+        }
     }
 }
 
+struct ObjUniforms {
+    view_position: Vec3,
+    light_direction: Vec3,
+    lighting_enabled: bool,
+    view: Mat4,
+    random_colors: bool,
+    double_sided: bool,
+    invert_normals: bool,
+    transparency: bool,
+    alpha_pass: AlphaPass,
+    texture: Option<glow::NativeTexture>,
+    matcap: Option<glow::NativeTexture>,
+    fill_color: Option<Vec3>,
+    point_size: f32,
+}
+
 fn draw_obj(
     gl: &glow::Context,
     vao: glow::NativeVertexArray,
     program: glow::Program,
-    mvp: &Mat4,
-    triangles_count: i32,
+    locations: &ObjUniformLocations,
+    uniforms: &ObjUniforms,
+    primitive: u32,
+    index_count: i32,
+) -> color_eyre::Result<()> {
+    unsafe {
+        gl.use_program(Some(program));
+
+        gl.uniform_3_f32_slice(
+            locations.view_position.as_ref(),
+            uniforms.view_position.to_array().as_slice(),
+        );
+        gl.uniform_3_f32_slice(
+            locations.light_direction.as_ref(),
+            uniforms.light_direction.to_array().as_slice(),
+        );
+        gl.uniform_1_i32(
+            locations.lighting_enabled.as_ref(),
+            uniforms.lighting_enabled as i32,
+        );
+        gl.uniform_1_i32(locations.matcap.as_ref(), uniforms.matcap.is_some() as i32);
+        gl.uniform_1_i32(locations.matcap_map.as_ref(), 1);
+        gl.uniform_matrix_4_f32_slice(
+            locations.view.as_ref(),
+            false,
+            &uniforms.view.to_cols_array(),
+        );
+        gl.uniform_1_i32(
+            locations.random_colors.as_ref(),
+            uniforms.random_colors as i32,
+        );
+        gl.uniform_1_i32(
+            locations.double_sided.as_ref(),
+            uniforms.double_sided as i32,
+        );
+        gl.uniform_1_i32(
+            locations.invert_normals.as_ref(),
+            uniforms.invert_normals as i32,
+        );
+        gl.uniform_1_i32(
+            locations.transparency.as_ref(),
+            uniforms.transparency as i32,
+        );
+        gl.uniform_1_i32(locations.alpha_pass.as_ref(), uniforms.alpha_pass as i32);
+        gl.uniform_1_i32(
+            locations.textured.as_ref(),
+            uniforms.texture.is_some() as i32,
+        );
+        gl.uniform_1_i32(locations.diffuse_map.as_ref(), 0);
+        gl.uniform_3_f32_slice(
+            locations.fill_color.as_ref(),
+            uniforms
+                .fill_color
+                .unwrap_or_default()
+                .to_array()
+                .as_slice(),
+        );
+        gl.uniform_1_i32(
+            locations.use_fill_color.as_ref(),
+            uniforms.fill_color.is_some() as i32,
+        );
+        gl.uniform_1_f32(locations.point_size.as_ref(), uniforms.point_size);
+
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, uniforms.texture);
+        gl.active_texture(glow::TEXTURE1);
+        gl.bind_texture(glow::TEXTURE_2D, uniforms.matcap);
+        gl.active_texture(glow::TEXTURE0);
+
+        gl.bind_vertex_array(Some(vao));
+        gl.draw_elements(primitive, index_count, glow::UNSIGNED_INT, 0);
+
+        Ok(())
+    }
+}
+
+/// Draws a line list uploaded by [`create_line_buffers`] as quads
+/// `line_width` pixels wide, blending their antialiased sides.
+fn draw_lines(
+    gl: &glow::Context,
+    vao: glow::NativeVertexArray,
+    program: glow::Program,
+    locations: &LineUniformLocations,
+    vertex_count: i32,
+    line_width: f32,
+    viewport_size: Vec2,
 ) -> color_eyre::Result<()> {
     unsafe {
         gl.use_program(Some(program));
 
-        let mvp_location = gl
-            .get_uniform_location(program, "mvp")
-            .wrap_err("no location for uniform")?;
+        gl.uniform_1_f32(locations.line_width.as_ref(), line_width);
+        gl.uniform_2_f32(
+            locations.viewport_size.as_ref(),
+            viewport_size.x,
+            viewport_size.y,
+        );
 
-        gl.uniform_matrix_4_f32_slice(Some(&mvp_location), false, mvp.to_cols_array().as_slice());
+        gl.enable(glow::BLEND);
+        gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
 
         gl.bind_vertex_array(Some(vao));
-        gl.draw_arrays(glow::TRIANGLES, 0, triangles_count);
+        gl.draw_arrays_instanced(glow::TRIANGLES, 0, 6, vertex_count / 2);
+
+        gl.disable(glow::BLEND);
 
         Ok(())
     }
 }
 
-fn draw_edges(
+/// Draws the back faces of the mesh inflated by `offset` along its normals,
+/// leaving a rim around the silhouette once the mesh is drawn over it.
+fn draw_outline(
     gl: &glow::Context,
     vao: glow::NativeVertexArray,
     program: glow::Program,
-    mvp: &Mat4,
-    line_count: i32,
+    offset_location: Option<&glow::UniformLocation>,
+    offset: f32,
+    index_count: i32,
 ) -> color_eyre::Result<()> {
     unsafe {
-        gl.line_width(2.0);
+        let culling = gl.is_enabled(glow::CULL_FACE);
 
         gl.use_program(Some(program));
+        gl.uniform_1_f32(offset_location, offset);
 
-        let mvp_location = gl
-            .get_uniform_location(program, "mvp")
-            .wrap_err("no location for uniform")?;
-
-        gl.uniform_matrix_4_f32_slice(Some(&mvp_location), false, mvp.to_cols_array().as_slice());
+        gl.enable(glow::CULL_FACE);
+        gl.cull_face(glow::FRONT);
 
         gl.bind_vertex_array(Some(vao));
-        gl.draw_arrays(glow::LINES, 0, line_count);
+        gl.draw_elements(glow::TRIANGLES, index_count, glow::UNSIGNED_INT, 0);
+
+        gl.cull_face(glow::BACK);
+        if !culling {
+            gl.disable(glow::CULL_FACE);
+        }
 
         Ok(())
     }
 }
 
-fn draw_axes(
+/// Blends the see-through faces of a mesh over what is already drawn without
+/// hiding what is drawn after them. Back faces go first so that the far side of
+/// the mesh shows through its near side.
+fn draw_transparent_obj(
+    gl: &glow::Context,
+    vao: glow::NativeVertexArray,
+    program: glow::Program,
+    locations: &ObjUniformLocations,
+    uniforms: &ObjUniforms,
+    index_count: i32,
+) -> color_eyre::Result<()> {
+    let culling = unsafe { gl.is_enabled(glow::CULL_FACE) };
+
+    unsafe {
+        gl.enable(glow::BLEND);
+        gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+        gl.depth_mask(false);
+        gl.enable(glow::CULL_FACE);
+    }
+
+    for face in [glow::FRONT, glow::BACK] {
+        unsafe { gl.cull_face(face) };
+
+        draw_obj(
+            gl,
+            vao,
+            program,
+            locations,
+            uniforms,
+            glow::TRIANGLES,
+            index_count,
+        )?;
+    }
+
+    unsafe {
+        if !culling {
+            gl.disable(glow::CULL_FACE);
+        }
+        gl.depth_mask(true);
+        gl.disable(glow::BLEND);
+    }
+
+    Ok(())
+}
+
+/// Draws the crosshair at the center of the screen, keeping its arms the same
+/// length in pixels whatever the aspect ratio.
+fn draw_crosshair(
     gl: &glow::Context,
     vao: glow::NativeVertexArray,
     program: glow::Program,
-    mvp: &Mat4,
+    scale_location: Option<&glow::UniformLocation>,
+    aspect: f32,
 ) -> color_eyre::Result<()> {
     unsafe {
         gl.use_program(Some(program));
 
-        let mvp_location = gl
-            .get_uniform_location(program, "mvp")
-            .wrap_err("no location for uniform")?;
+        gl.uniform_2_f32(scale_location, CROSSHAIR_SIZE / aspect, CROSSHAIR_SIZE);
+
+        gl.bind_vertex_array(Some(vao));
+        gl.draw_arrays(glow::LINES, 0, (CROSSHAIR_DATA.len() / 3) as i32);
+
+        Ok(())
+    }
+}
+
+/// Draws a highlighted triangle over the mesh, pulled towards the camera so
+/// that it doesn't fight with the triangle below for depth.
+fn draw_highlight(
+    gl: &glow::Context,
+    vao: glow::NativeVertexArray,
+    program: glow::Program,
+) -> color_eyre::Result<()> {
+    unsafe {
+        gl.use_program(Some(program));
 
-        gl.uniform_matrix_4_f32_slice(Some(&mvp_location), false, mvp.to_cols_array().as_slice());
+        gl.enable(glow::POLYGON_OFFSET_FILL);
+        gl.polygon_offset(-1.0, -1.0);
 
         gl.bind_vertex_array(Some(vao));
-        gl.draw_arrays(glow::LINES, 0, 6);
+        gl.draw_arrays(glow::TRIANGLES, 0, 3);
+
+        gl.disable(glow::POLYGON_OFFSET_FILL);
 
         Ok(())
     }
 }
+
+/// Looks up a uniform once after linking. A missing uniform, possibly
+/// optimized out by the driver, is logged at debug level and then silently not
+/// set.
+fn uniform_location(
+    gl: &glow::Context,
+    program: glow::Program,
+    name: &str,
+) -> Option<glow::UniformLocation> {
+    let location = unsafe { gl.get_uniform_location(program, name) };
+
+    if location.is_none() {
+        debug!("no location for uniform {name}");
+    }
+
+    location
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Context;
+use glam::Vec3;
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub diffuse: Vec3,
+    /// Diffuse texture from `map_Kd`, relative to the working directory.
+    pub diffuse_map: Option<PathBuf>,
+    /// From 0 for invisible to 1 for opaque, read from `d` or `Tr`.
+    pub opacity: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            diffuse: Vec3::splat(0.8),
+            diffuse_map: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Parses the materials declared in a `.mtl` library, keyed by name.
+pub fn load_material_library(path: &Path) -> color_eyre::Result<HashMap<String, Material>> {
+    let source = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("cannot read material library {}", path.display()))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    let mut materials = HashMap::new();
+    let mut current = None;
+
+    for line in source.lines() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("newmtl") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                materials.insert(name.clone(), Material::default());
+                current = Some(name);
+            }
+            Some("Kd") => {
+                let Some(material) = current.as_ref().and_then(|name| materials.get_mut(name))
+                else {
+                    continue;
+                };
+
+                let components = tokens
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .wrap_err_with(|| format!("invalid Kd in {}", path.display()))?;
+
+                if let [r, g, b] = components[..] {
+                    material.diffuse = Vec3::new(r, g, b);
+                }
+            }
+            Some(keyword @ ("d" | "Tr")) => {
+                let Some(material) = current.as_ref().and_then(|name| materials.get_mut(name))
+                else {
+                    continue;
+                };
+
+                // `d` may start with `-halo`, the value coming last. `Tr` is
+                // its complement, used by some exporters.
+                let value = tokens
+                    .last()
+                    .map(str::parse::<f32>)
+                    .transpose()
+                    .wrap_err_with(|| format!("invalid {keyword} in {}", path.display()))?;
+
+                if let Some(value) = value {
+                    let opacity = if keyword == "Tr" { 1.0 - value } else { value };
+                    material.opacity = opacity.clamp(0.0, 1.0);
+                }
+            }
+            Some("map_Kd") => {
+                let Some(material) = current.as_ref().and_then(|name| materials.get_mut(name))
+                else {
+                    continue;
+                };
+
+                // Options such as `-s` come first, the file name last.
+                if let Some(file_name) = tokens.last() {
+                    material.diffuse_map = Some(base_dir.join(file_name));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+/// A face of an OBJ file, as read by [`obj_faces`].
+#[derive(Debug, Clone)]
+pub struct Face {
+    pub vertex_count: usize,
+    /// `None` without a material, or with an unknown one.
+    pub material: Option<Material>,
+}
+
+/// Every face of the OBJ `source`, in file order, which is also the order of
+/// `wavefront::Obj::vertices`. Libraries are looked up next to `obj_path`.
+///
+/// The wavefront crate only exposes geometry, so `mtllib` and `usemtl`
+/// statements are read here directly. Its own face iterators go through the
+/// objects and groups in hash map order, and repeat faces belonging to
+/// several groups, so they can't be matched with the materials.
+pub fn obj_faces(source: &str, obj_path: &Path) -> Vec<Face> {
+    let base_dir = obj_path.parent().unwrap_or(Path::new("."));
+
+    let mut materials = HashMap::new();
+    let mut current = None;
+    let mut faces = Vec::new();
+
+    for line in source.lines() {
+        // Split as the wavefront crate does, to count the same face vertices.
+        let mut tokens = line.split_ascii_whitespace();
+
+        match tokens.next() {
+            Some("mtllib") => {
+                for library in tokens {
+                    match load_material_library(&base_dir.join(library)) {
+                        Ok(library) => materials.extend(library),
+                        Err(err) => warn!("{err:#}"),
+                    }
+                }
+            }
+            Some("usemtl") => {
+                // Names may contain spaces, as in `newmtl`.
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                current = Some(name).filter(|name| !name.is_empty());
+            }
+            Some("f") => {
+                let material = current
+                    .as_ref()
+                    .and_then(|name| materials.get(name))
+                    .cloned();
+
+                faces.push(Face {
+                    vertex_count: tokens.count(),
+                    material,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    faces
+}
@@ -0,0 +1,700 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Context, ContextCompat, bail, eyre};
+use glam::{Mat4, Vec3};
+use ply_rs::ply::Property;
+
+use crate::material;
+
+const DEFAULT_COLOR: Vec3 = Vec3::splat(0.41);
+
+/// Lowercase extensions of the supported model formats.
+pub const EXTENSIONS: [&str; 3] = ["obj", "stl", "ply"];
+
+/// Triangle soup loaded from a model file.
+///
+/// Attributes are flattened per vertex, three floats each except for the
+/// four of colors, with three consecutive vertices per triangle.
+pub struct Mesh {
+    pub positions: Vec<f32>,
+    /// Normals read from the file, only present if every vertex has one.
+    pub normals: Option<Vec<f32>>,
+    /// Colors with the opacity of their material as alpha.
+    pub colors: Vec<f32>,
+    /// Texture coordinates followed by the weight of the texture over the
+    /// color, 1 for textured triangles and 0 otherwise.
+    pub tex_coords: Vec<f32>,
+    /// Diffuse texture of the textured triangles.
+    pub texture: Option<PathBuf>,
+    /// Opacity of the most transparent material, 1 for opaque meshes.
+    pub opacity: f32,
+    pub bounds: BoundingBox,
+    /// Whether the vertices are unconnected points, from a file without any
+    /// face, rather than triangles.
+    pub point_cloud: bool,
+}
+
+impl Mesh {
+    /// Loads a mesh, picking the format from the file extension and falling
+    /// back to Wavefront OBJ.
+    ///
+    /// A path of `-` reads a Wavefront OBJ from the standard input instead.
+    pub fn from_file(path: &Path) -> color_eyre::Result<Self> {
+        let mesh = if path == Path::new("-") {
+            Self::from_obj_stdin()?
+        } else {
+            if !path.is_file() {
+                bail!("no such file: {}", path.display());
+            }
+
+            let extension = path
+                .extension()
+                .and_then(OsStr::to_str)
+                .map(str::to_ascii_lowercase);
+
+            match extension.as_deref() {
+                Some("stl") => Self::from_stl_file(path)?,
+                Some("ply") => Self::from_ply_file(path)?,
+                _ => Self::from_obj_file(path)?,
+            }
+        };
+
+        // Nothing could be framed or drawn, and the bounds would be infinite.
+        if mesh.vertex_count() == 0 {
+            bail!("mesh contains no triangles or points: {}", path.display());
+        }
+
+        Ok(mesh)
+    }
+
+    /// Loads a Wavefront OBJ file along with the diffuse colors of its
+    /// materials, or its vertex colors when every vertex has one.
+    ///
+    /// Only one texture is supported per mesh: the first `map_Kd` used by a
+    /// face textures every face of the materials sharing it.
+    ///
+    /// Quads and larger polygons are fan-triangulated, so a face of `n`
+    /// vertices yields `n - 2` triangles, in file order. A file with vertices
+    /// but no faces gives a point cloud.
+    pub fn from_obj_file(path: &Path) -> color_eyre::Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("cannot read {}", path.display()))?;
+
+        Self::from_obj_source(&source, path)
+    }
+
+    /// Loads a Wavefront OBJ piped to the standard input, looking up its
+    /// material libraries in the working directory.
+    pub fn from_obj_stdin() -> color_eyre::Result<Self> {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .wrap_err("cannot read the standard input")?;
+
+        Self::from_obj_source(&source, Path::new("<stdin>"))
+    }
+
+    /// Parses the contents of an OBJ file, `path` locating its material
+    /// libraries and error messages.
+    fn from_obj_source(source: &str, path: &Path) -> color_eyre::Result<Self> {
+        let obj = wavefront::Obj::from_lines(source.lines()).map_err(|err| {
+            let location = match err {
+                wavefront::Error::ExpectedTerm(line)
+                | wavefront::Error::ExpectedIdx(line)
+                | wavefront::Error::ExpectedName(line) => format!("{}:{line}", path.display()),
+                _ => path.display().to_string(),
+            };
+
+            eyre!(err).wrap_err(format!("cannot parse Wavefront file {location}"))
+        })?;
+        let faces = material::obj_faces(source, path);
+        let vertex_colors = read_vertex_colors(source);
+        let materials = || faces.iter().filter_map(|face| face.material.as_ref());
+        let texture = materials().find_map(|material| material.diffuse_map.clone());
+        let opacity = materials()
+            .map(|material| material.opacity)
+            .fold(1.0, f32::min);
+
+        let has_triangles = faces.iter().any(|face| face.vertex_count >= 3);
+        if !has_triangles && !obj.positions().is_empty() {
+            let positions = obj
+                .positions()
+                .iter()
+                .map(|&position| Vec3::from_array(position));
+            let colors =
+                vertex_colors.unwrap_or_else(|| vec![DEFAULT_COLOR; obj.positions().len()]);
+
+            return Ok(Self::from_points(positions, colors));
+        }
+
+        // Faces are fanned in file order, as the crate would triangulate them,
+        // so that each triangle gets the material of its own face.
+        let face_vertices = obj.vertices().collect::<Vec<_>>();
+        let mut face_start = 0;
+        let mut vertices = Vec::new();
+
+        for face in &faces {
+            let corners = face_vertices
+                .get(face_start..face_start + face.vertex_count)
+                .wrap_err_with(|| {
+                    format!("{}: faces don't match the parsed vertices", path.display())
+                })?;
+            face_start += face.vertex_count;
+
+            let material = face.material.as_ref();
+            let color = material.map_or(DEFAULT_COLOR, |material| material.diffuse);
+            let alpha = material.map_or(1.0, |material| material.opacity);
+            let textured = texture.is_some()
+                && material.is_some_and(|material| material.diffuse_map == texture);
+
+            for i in 1..corners.len().saturating_sub(1) {
+                for vertex in [corners[0], corners[i], corners[i + 1]] {
+                    let [u, v, _] = vertex.uv().unwrap_or_default();
+                    let color = vertex_colors
+                        .as_ref()
+                        .and_then(|colors| colors.get(vertex.position_index()))
+                        .copied()
+                        .unwrap_or(color)
+                        .extend(alpha);
+
+                    vertices.push((
+                        Vec3::from_array(vertex.position()),
+                        vertex.normal().map(Vec3::from_array),
+                        color,
+                        Vec3::new(u, v, if textured { 1.0 } else { 0.0 }),
+                    ));
+                }
+            }
+        }
+
+        let mut bounds = BoundingBox::empty();
+        for &(position, _, _, _) in &vertices {
+            bounds.extend(position);
+        }
+
+        let positions = vertices
+            .iter()
+            .flat_map(|(position, _, _, _)| position.to_array())
+            .collect();
+        // Partial normals are dropped, smooth ones get computed instead.
+        let normals = vertices
+            .iter()
+            .map(|(_, normal, _, _)| normal.map(|normal| normal.to_array()))
+            .collect::<Option<Vec<_>>>()
+            .map(|normals| normals.concat());
+        let colors = vertices
+            .iter()
+            .flat_map(|(_, _, color, _)| color.to_array())
+            .collect();
+        let tex_coords = vertices
+            .iter()
+            .flat_map(|(_, _, _, tex_coord)| tex_coord.to_array())
+            .collect();
+
+        Ok(Self {
+            positions,
+            normals,
+            colors,
+            tex_coords,
+            texture,
+            opacity,
+            bounds,
+            point_cloud: false,
+        })
+    }
+
+    /// Loads an ASCII or binary STL file, shading each facet with its normal.
+    pub fn from_stl_file(path: &Path) -> color_eyre::Result<Self> {
+        let mut file =
+            File::open(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+        let stl = stl_io::read_stl(&mut file)
+            .wrap_err_with(|| format!("cannot parse STL file {}", path.display()))?;
+
+        let mut positions = Vec::with_capacity(stl.faces.len() * 9);
+        let mut normals = Vec::with_capacity(stl.faces.len() * 9);
+        let mut bounds = BoundingBox::empty();
+
+        for face in &stl.faces {
+            let corners = face.vertices.map(|index| {
+                let vertex = &stl.vertices[index];
+                Vec3::new(vertex[0], vertex[1], vertex[2])
+            });
+
+            // Some exporters leave the facet normal zeroed out.
+            let stored_normal = Vec3::new(face.normal[0], face.normal[1], face.normal[2]);
+            let normal = if stored_normal.length_squared() > 0.0 {
+                stored_normal.normalize()
+            } else {
+                (corners[1] - corners[0])
+                    .cross(corners[2] - corners[0])
+                    .normalize_or_zero()
+            };
+
+            for corner in corners {
+                bounds.extend(corner);
+                positions.extend_from_slice(&corner.to_array());
+                normals.extend_from_slice(&normal.to_array());
+            }
+        }
+
+        let colors = DEFAULT_COLOR
+            .extend(1.0)
+            .to_array()
+            .repeat(positions.len() / 3);
+        let tex_coords = vec![0.0; positions.len()];
+
+        Ok(Self {
+            positions,
+            normals: Some(normals),
+            colors,
+            tex_coords,
+            texture: None,
+            opacity: 1.0,
+            bounds,
+            point_cloud: false,
+        })
+    }
+
+    /// Loads an ASCII or binary PLY file, fan-triangulating polygonal faces.
+    pub fn from_ply_file(path: &Path) -> color_eyre::Result<Self> {
+        let file = File::open(path).wrap_err_with(|| format!("cannot open {}", path.display()))?;
+        let parser = ply_rs::parser::Parser::<ply_rs::ply::DefaultElement>::new();
+        let ply = parser
+            .read_ply(&mut BufReader::new(file))
+            .wrap_err_with(|| format!("cannot parse PLY file {}", path.display()))?;
+
+        let vertices = ply
+            .payload
+            .get("vertex")
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|vertex| {
+                let coordinate = |name: &str| vertex.get(name).and_then(property_as_f32);
+
+                Some(Vec3::new(
+                    coordinate("x")?,
+                    coordinate("y")?,
+                    coordinate("z")?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+            .wrap_err_with(|| format!("{}: vertex without x/y/z coordinates", path.display()))?;
+
+        let has_faces = ply
+            .payload
+            .get("face")
+            .is_some_and(|faces| !faces.is_empty());
+
+        if !has_faces && !vertices.is_empty() {
+            let colors = vec![DEFAULT_COLOR; vertices.len()];
+            return Ok(Self::from_points(vertices, colors));
+        }
+
+        let mut positions = Vec::new();
+        let mut bounds = BoundingBox::empty();
+
+        for face in ply
+            .payload
+            .get("face")
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            let indices = face
+                .get("vertex_indices")
+                .or_else(|| face.get("vertex_index"))
+                .and_then(property_as_indices)
+                .wrap_err_with(|| format!("{}: face without vertex indices", path.display()))?;
+
+            let corners = indices
+                .iter()
+                .map(|&index| vertices.get(index).copied())
+                .collect::<Option<Vec<_>>>()
+                .wrap_err_with(|| format!("{}: face index out of range", path.display()))?;
+
+            for i in 1..corners.len().saturating_sub(1) {
+                for corner in [corners[0], corners[i], corners[i + 1]] {
+                    bounds.extend(corner);
+                    positions.extend_from_slice(&corner.to_array());
+                }
+            }
+        }
+
+        let colors = DEFAULT_COLOR
+            .extend(1.0)
+            .to_array()
+            .repeat(positions.len() / 3);
+        let tex_coords = vec![0.0; positions.len()];
+
+        Ok(Self {
+            positions,
+            normals: None,
+            colors,
+            tex_coords,
+            texture: None,
+            opacity: 1.0,
+            bounds,
+            point_cloud: false,
+        })
+    }
+
+    /// Builds a point cloud, one vertex per point.
+    fn from_points(points: impl IntoIterator<Item = Vec3>, colors: Vec<Vec3>) -> Self {
+        let mut positions = Vec::new();
+        let mut bounds = BoundingBox::empty();
+
+        for point in points {
+            bounds.extend(point);
+            positions.extend_from_slice(&point.to_array());
+        }
+
+        let colors = colors
+            .iter()
+            .flat_map(|color| color.extend(1.0).to_array())
+            .collect::<Vec<_>>();
+        let tex_coords = vec![0.0; positions.len()];
+
+        Self {
+            positions,
+            normals: None,
+            colors,
+            tex_coords,
+            texture: None,
+            opacity: 1.0,
+            bounds,
+            point_cloud: true,
+        }
+    }
+
+    /// Keeps at most `max_triangles` triangles, evenly spread over the mesh by
+    /// taking every n-th one. This is a quick preview rather than a
+    /// simplification: holes appear, and the bounds are kept so that the view
+    /// frames the whole mesh.
+    pub fn subsample(&mut self, max_triangles: usize) {
+        let triangle_count = self.triangle_count();
+        if triangle_count <= max_triangles {
+            return;
+        }
+
+        // `size` is the number of floats per vertex.
+        let keep = |data: &[f32], size: usize| {
+            (0..max_triangles)
+                .map(|index| index * triangle_count / max_triangles)
+                .flat_map(|triangle| &data[triangle * 3 * size..][..3 * size])
+                .copied()
+                .collect::<Vec<_>>()
+        };
+
+        self.positions = keep(&self.positions, 3);
+        self.normals = self.normals.as_deref().map(|normals| keep(normals, 3));
+        self.colors = keep(&self.colors, 4);
+        self.tex_coords = keep(&self.tex_coords, 3);
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        if self.point_cloud {
+            0
+        } else {
+            self.positions.len() / 9
+        }
+    }
+
+    /// Geometric normal of each triangle, repeated for its three vertices.
+    /// Points have no normal and get zero vectors.
+    pub fn face_normals(&self) -> Vec<f32> {
+        if self.point_cloud {
+            return vec![0.0; self.positions.len()];
+        }
+
+        self.positions
+            .chunks_exact(9)
+            .flat_map(|triangle| {
+                let v0 = Vec3::from_slice(&triangle[0..3]);
+                let v1 = Vec3::from_slice(&triangle[3..6]);
+                let v2 = Vec3::from_slice(&triangle[6..9]);
+
+                (v1 - v0)
+                    .cross(v2 - v0)
+                    .normalize_or_zero()
+                    .to_array()
+                    .repeat(3)
+            })
+            .collect()
+    }
+
+    /// Normals from the file if it has them, normals computed from the
+    /// geometry otherwise, smoothed across edges sharper than `crease_angle`
+    /// degrees.
+    pub fn vertex_normals(&self, crease_angle: f32) -> Vec<f32> {
+        match &self.normals {
+            Some(normals) => normals.clone(),
+            None if self.point_cloud => self.face_normals(),
+            None => compute_vertex_normals(&self.positions, crease_angle),
+        }
+    }
+}
+
+/// Colors written after the coordinates of `v` statements, an extension of
+/// the OBJ format the wavefront crate ignores. Only returned when every vertex
+/// has one, in the order of the positions.
+fn read_vertex_colors(source: &str) -> Option<Vec<Vec3>> {
+    let colors = source
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            (tokens.next() == Some("v")).then_some(tokens)
+        })
+        .map(|tokens| {
+            let values = tokens
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?;
+
+            match values[..] {
+                [_, _, _, r, g, b] => Some(Vec3::new(r, g, b)),
+                _ => None,
+            }
+        })
+        .collect::<Option<Vec<_>>>();
+
+    colors.filter(|colors| !colors.is_empty())
+}
+
+fn property_as_f32(property: &Property) -> Option<f32> {
+    match *property {
+        Property::Float(value) => Some(value),
+        Property::Double(value) => Some(value as f32),
+        _ => None,
+    }
+}
+
+fn property_as_indices(property: &Property) -> Option<Vec<usize>> {
+    match property {
+        Property::ListUChar(indices) => Some(indices.iter().map(|&i| i as usize).collect()),
+        Property::ListUShort(indices) => Some(indices.iter().map(|&i| i as usize).collect()),
+        Property::ListUInt(indices) => Some(indices.iter().map(|&i| i as usize).collect()),
+        Property::ListChar(indices) => indices.iter().map(|&i| i.try_into().ok()).collect(),
+        Property::ListShort(indices) => indices.iter().map(|&i| i.try_into().ok()).collect(),
+        Property::ListInt(indices) => indices.iter().map(|&i| i.try_into().ok()).collect(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3::splat(f32::INFINITY),
+            max: Vec3::splat(f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn extend(&mut self, point: Vec3) {
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).length() * 0.5
+    }
+
+    /// Bounds of the box once transformed, enclosing all eight of its corners
+    /// so that rotations are covered.
+    pub fn transformed(&self, transform: Mat4) -> Self {
+        let mut bounds = Self::empty();
+
+        for index in 0..8 {
+            let corner = Vec3::select(
+                glam::BVec3::new(index & 1 != 0, index & 2 != 0, index & 4 != 0),
+                self.max,
+                self.min,
+            );
+            bounds.extend(transform.transform_point3(corner));
+        }
+
+        bounds
+    }
+}
+
+/// Line-list vertices for every distinct triangle edge.
+///
+/// Vertices are identified by their exact position, so an edge shared by two
+/// triangles is only emitted once.
+pub fn extract_edges_from_triangles(vertex_data: &[f32]) -> Vec<f32> {
+    let mut edge_data = Vec::new();
+
+    for_each_edge(vertex_data, |a, b| {
+        edge_data.extend_from_slice(&a);
+        edge_data.extend_from_slice(&b);
+    });
+
+    edge_data
+}
+
+/// Number of distinct triangle edges, as extracted by
+/// [`extract_edges_from_triangles`].
+pub fn count_edges(vertex_data: &[f32]) -> usize {
+    let mut count = 0;
+    for_each_edge(vertex_data, |_, _| count += 1);
+
+    count
+}
+
+/// Calls `emit` with the end points of every distinct triangle edge.
+fn for_each_edge(vertex_data: &[f32], mut emit: impl FnMut([f32; 3], [f32; 3])) {
+    let mut positions = Vec::new();
+    let mut position_indices = HashMap::<[u32; 3], u32>::new();
+    let mut edges = HashSet::new();
+
+    for triangle in vertex_data.chunks_exact(9) {
+        let corners = [&triangle[0..3], &triangle[3..6], &triangle[6..9]].map(|position| {
+            let position = [position[0], position[1], position[2]];
+
+            *position_indices
+                .entry(position.map(f32::to_bits))
+                .or_insert_with(|| {
+                    positions.push(position);
+                    (positions.len() - 1) as u32
+                })
+        });
+
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            let (a, b) = (corners[a], corners[b]);
+
+            if a != b && edges.insert((a.min(b), a.max(b))) {
+                emit(positions[a as usize], positions[b as usize]);
+            }
+        }
+    }
+}
+
+/// Per-vertex normals, averaged over the triangles sharing a position whose
+/// normal is within `crease_angle` degrees of the triangle's own, so that
+/// sharper edges stay hard.
+///
+/// Face normals are accumulated unnormalized so that larger triangles weigh
+/// more in the result.
+fn compute_vertex_normals(vertex_data: &[f32], crease_angle: f32) -> Vec<f32> {
+    let key = |position: Vec3| position.to_array().map(f32::to_bits);
+    let min_cosine = crease_angle.to_radians().cos();
+
+    let face_normals = vertex_data
+        .chunks_exact(9)
+        .map(|triangle| {
+            let v0 = Vec3::from_slice(&triangle[0..3]);
+            let v1 = Vec3::from_slice(&triangle[3..6]);
+            let v2 = Vec3::from_slice(&triangle[6..9]);
+
+            (v1 - v0).cross(v2 - v0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut adjacent_faces = HashMap::<[u32; 3], Vec<usize>>::new();
+
+    for (vertex, position) in vertex_data.chunks_exact(3).enumerate() {
+        adjacent_faces
+            .entry(key(Vec3::from_slice(position)))
+            .or_default()
+            .push(vertex / 3);
+    }
+
+    vertex_data
+        .chunks_exact(3)
+        .enumerate()
+        .flat_map(|(vertex, position)| {
+            let face_normal = face_normals[vertex / 3];
+            let direction = face_normal.normalize_or_zero();
+
+            adjacent_faces[&key(Vec3::from_slice(position))]
+                .iter()
+                .map(|&face| face_normals[face])
+                .filter(|normal| direction.dot(normal.normalize_or_zero()) >= min_cosine)
+                .fold(Vec3::ZERO, |sum, normal| sum + normal)
+                .normalize_or_zero()
+                .to_array()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_edges_are_shared() {
+        // Two triangles sharing their diagonal.
+        let positions = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+
+        let edges = extract_edges_from_triangles(&positions);
+
+        assert_eq!(edges.len(), 5 * 6);
+    }
+
+    #[test]
+    fn quad_face_yields_two_triangles() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+
+        let mesh = Mesh::from_obj_source(source, Path::new("quad.obj")).unwrap();
+
+        assert_eq!(mesh.triangle_count(), 2);
+    }
+
+    #[test]
+    fn empty_obj_is_rejected() {
+        let path = std::env::temp_dir().join(format!("objviewer-empty-{}.obj", std::process::id()));
+        std::fs::write(&path, "").unwrap();
+
+        let result = Mesh::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let Err(err) = result else {
+            panic!("an empty OBJ file was loaded");
+        };
+        let message = err.to_string();
+        assert!(
+            message.starts_with("mesh contains no triangles or points"),
+            "unexpected error: {message}"
+        );
+    }
+
+    #[test]
+    fn cube_normals_stay_sharp() {
+        let source = "\
+            v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            v 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+            f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\n\
+            f 4 8 7 3\nf 1 5 8 4\nf 2 3 7 6\n";
+        let mesh = Mesh::from_obj_source(source, Path::new("cube.obj")).unwrap();
+
+        let normals = compute_vertex_normals(&mesh.positions, 30.0);
+
+        assert_eq!(normals, mesh.face_normals());
+        for normal in normals.chunks_exact(3) {
+            let normal = Vec3::from_slice(normal);
+            assert_eq!(normal.abs().max_element(), 1.0);
+            assert_eq!(normal.abs().element_sum(), 1.0);
+        }
+    }
+}
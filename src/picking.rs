@@ -0,0 +1,109 @@
+use glam::{Mat4, Vec2, Vec3, Vec4Swizzles};
+
+/// Half-line used to find what lies under the cursor.
+///
+/// The direction isn't normalized, so that distances along the ray stay
+/// comparable after transforming it with an affine matrix.
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Ray going from the near plane to the far plane through a point of the
+    /// window, in pixels from its top left corner.
+    pub fn from_cursor(cursor: Vec2, window_size: Vec2, view_projection: Mat4) -> Self {
+        let ndc = Vec2::new(
+            cursor.x / window_size.x * 2.0 - 1.0,
+            1.0 - cursor.y / window_size.y * 2.0,
+        );
+
+        let inverse = view_projection.inverse();
+        let unproject = |depth: f32| {
+            let point = inverse * ndc.extend(depth).extend(1.0);
+            point.xyz() / point.w
+        };
+
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+
+        Self {
+            origin: near,
+            direction: far - near,
+        }
+    }
+
+    pub fn transformed(&self, transform: Mat4) -> Self {
+        Self {
+            origin: transform.transform_point3(self.origin),
+            direction: transform.transform_vector3(self.direction),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance along the ray to the plane through `point` facing `normal`,
+    /// missing when the ray is parallel to it or points away from it.
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<f32> {
+        let denominator = self.direction.dot(normal);
+
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let distance = (point - self.origin).dot(normal) / denominator;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// Nearest triangle hit by the ray, as its index and the distance to it.
+    ///
+    /// `positions` is a flattened triangle soup, three floats per vertex.
+    pub fn intersect_triangles(&self, positions: &[f32]) -> Option<(usize, f32)> {
+        positions
+            .chunks_exact(9)
+            .enumerate()
+            .filter_map(|(index, triangle)| {
+                let distance = self.intersect_triangle(
+                    Vec3::from_slice(&triangle[0..3]),
+                    Vec3::from_slice(&triangle[3..6]),
+                    Vec3::from_slice(&triangle[6..9]),
+                )?;
+
+                Some((index, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Möller-Trumbore intersection, hitting both faces of the triangle.
+    fn intersect_triangle(&self, v0: Vec3, v1: Vec3, v2: Vec3) -> Option<f32> {
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let to_origin = self.origin - v0;
+
+        let u = to_origin.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(edge1);
+        let v = self.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge2.dot(q) * inverse_determinant;
+        (distance >= 0.0).then_some(distance)
+    }
+}
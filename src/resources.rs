@@ -0,0 +1,84 @@
+use glow::HasContext;
+
+/// A vertex array and the buffers bound to it, deleted together on drop.
+pub struct VertexArray<'gl> {
+    gl: &'gl glow::Context,
+    pub vao: glow::NativeVertexArray,
+    buffers: Vec<glow::NativeBuffer>,
+}
+
+impl<'gl> VertexArray<'gl> {
+    pub fn new(
+        gl: &'gl glow::Context,
+        vao: glow::NativeVertexArray,
+        buffers: Vec<glow::NativeBuffer>,
+    ) -> Self {
+        Self { gl, vao, buffers }
+    }
+}
+
+impl Drop for VertexArray<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_vertex_array(self.vao);
+
+            for &buffer in &self.buffers {
+                self.gl.delete_buffer(buffer);
+            }
+        }
+    }
+}
+
+/// A linked shader program, deleted on drop.
+pub struct ShaderProgram<'gl> {
+    gl: &'gl glow::Context,
+    pub program: glow::Program,
+}
+
+impl<'gl> ShaderProgram<'gl> {
+    pub fn new(gl: &'gl glow::Context, program: glow::Program) -> Self {
+        Self { gl, program }
+    }
+}
+
+impl Drop for ShaderProgram<'_> {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_program(self.program) };
+    }
+}
+
+/// A texture object, deleted on drop.
+pub struct Texture<'gl> {
+    gl: &'gl glow::Context,
+    pub texture: glow::NativeTexture,
+}
+
+impl<'gl> Texture<'gl> {
+    pub fn new(gl: &'gl glow::Context, texture: glow::NativeTexture) -> Self {
+        Self { gl, texture }
+    }
+}
+
+impl Drop for Texture<'_> {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_texture(self.texture) };
+    }
+}
+
+/// A standalone buffer object, deleted on drop.
+pub struct Buffer<'gl> {
+    gl: &'gl glow::Context,
+    pub buffer: glow::NativeBuffer,
+}
+
+impl<'gl> Buffer<'gl> {
+    pub fn new(gl: &'gl glow::Context, buffer: glow::NativeBuffer) -> Self {
+        Self { gl, buffer }
+    }
+}
+
+impl Drop for Buffer<'_> {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_buffer(self.buffer) };
+    }
+}
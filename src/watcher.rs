@@ -0,0 +1,68 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::Context;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+/// How long the file must stay quiet before a change is reported, so that
+/// editors writing a file in several steps only trigger one reload.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches a single file for modifications.
+pub struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    path: PathBuf,
+    pending_since: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(path: &Path) -> color_eyre::Result<Self> {
+        let path = path
+            .canonicalize()
+            .wrap_err_with(|| format!("cannot resolve {}", path.display()))?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).wrap_err("cannot create watcher")?;
+
+        // Watch the parent directory rather than the file itself: many editors
+        // save by writing a new file and renaming it over the old one.
+        let directory = path.parent().unwrap_or(Path::new("/"));
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .wrap_err_with(|| format!("cannot watch {}", directory.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            path,
+            pending_since: None,
+        })
+    }
+
+    /// Returns `true` once after the file changed and then stayed untouched
+    /// for [`DEBOUNCE`].
+    pub fn poll(&mut self) -> bool {
+        for event in self.events.try_iter() {
+            let Ok(event) = event else {
+                continue;
+            };
+
+            let is_change = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+            if is_change && event.paths.contains(&self.path) {
+                self.pending_since = Some(Instant::now());
+            }
+        }
+
+        match self.pending_since {
+            Some(since) if since.elapsed() >= DEBOUNCE => {
+                self.pending_since = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}